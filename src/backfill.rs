@@ -0,0 +1,173 @@
+use anyhow::anyhow;
+use rusqlite::Connection;
+
+use crate::chain::{aura_slot_from_header, authorities_at, ChainSource};
+use crate::render::{format_ts, hex0x, hex32, OutputTz};
+use crate::schedule::{compute_my_slots, hash_authorities, planned_ts_ms};
+use crate::store::{db_insert_schedule, db_replace_epoch_authorities, db_upsert_epoch_info, SCHEDULE_SOURCE_AURA};
+use crate::watch::record_finalized_block;
+
+/// Totals of one [`backfill_blocks`] run.
+#[derive(Default)]
+pub struct BackfillSummary {
+	pub blocks_scanned: u64,
+	pub epochs: u64,
+	pub scheduled_slots: u64,
+}
+
+/// Rebuild history for the finalized blocks `from_block..=to_block` (clamped to the finalized head).
+///
+/// For every epoch the walk enters, the authority set in the state of its first walked block gives
+/// `epoch_info` and each author's scheduled slots, as the watch loop reads it at a head inside the epoch
+/// (the parent may still hold the previous set: the session rotates in the epoch's first block). The blocks
+/// are then applied like the watch loop's finality scan, so our slots end up `finality` or `missed`.
+/// Historical state is required, i.e. an archive node.
+pub fn backfill_blocks<C: ChainSource>(
+	src: &C,
+	conn: &mut Connection,
+	authors: &[[u8; 32]],
+	epoch_size: u64,
+	from_block: u64,
+	to_block: u64,
+	mut on_block: impl FnMut(u64, u64),
+) -> anyhow::Result<BackfillSummary> {
+	let to_block = to_block.min(src.finalized_number()?);
+	if from_block > to_block {
+		return Err(anyhow!("empty block range: {from_block}..={to_block} (to is clamped to the finalized head)"));
+	}
+	let slot_dur_ms = src.slot_duration_ms()?;
+
+	// Only slots inside the walked range are scheduled, so the range edges are not reported as missed.
+	let mut prev_slot = match from_block.checked_sub(1) {
+		Some(n) => src.header_by_number(n)?.and_then(|(_, hdr)| aura_slot_from_header(&hdr)),
+		None => None,
+	};
+	let last_slot = src
+		.header_by_number(to_block)?
+		.and_then(|(_, hdr)| aura_slot_from_header(&hdr))
+		.ok_or_else(|| anyhow!("no Aura slot for block {to_block}"))?;
+
+	let mut summary = BackfillSummary::default();
+	let mut current_epoch: Option<u64> = None;
+	for n in from_block..=to_block {
+		on_block(n, to_block);
+		let Some((h, hdr)) = src.header_by_number(n)? else {
+			continue;
+		};
+		let Some(slot) = aura_slot_from_header(&hdr) else {
+			continue;
+		};
+		summary.blocks_scanned += 1;
+
+		let epoch = slot / epoch_size;
+		if current_epoch != Some(epoch) {
+			let auths = authorities_at(src, h)
+				.map_err(|e| anyhow!("authorities at {h:?} unavailable (archive node required): {e}"))?;
+			let start_slot = epoch * epoch_size;
+			let end_slot = start_slot + epoch_size.saturating_sub(1);
+			let auth_hash_hex = hex32(hash_authorities(&auths));
+			let first_slot = prev_slot.map(|p| p + 1).unwrap_or(slot);
+			let ts_ms = src.timestamp_ms(Some(h))?.unwrap_or(slot * slot_dur_ms);
+			let auth_hexes: Vec<String> = auths.iter().map(|a| hex0x(a.as_ref())).collect();
+			db_replace_epoch_authorities(conn, epoch, &auth_hexes)?;
+			for author_bytes in authors {
+				let author = hex0x(author_bytes);
+				db_upsert_epoch_info(conn, epoch, &author, start_slot, end_slot, &auth_hash_hex, auths.len())?;
+				let planned: Vec<(u64, String)> = compute_my_slots(&auths, author_bytes, start_slot, epoch_size)
+					.into_iter()
+					.filter(|s| (first_slot..=last_slot).contains(s))
+					.map(|s| (s, format_ts(planned_ts_ms(s, slot, ts_ms, slot_dur_ms), &OutputTz::Utc)))
+					.collect();
+				db_insert_schedule(conn, epoch, &author, &planned, SCHEDULE_SOURCE_AURA)?;
+				summary.scheduled_slots += planned.len() as u64;
+			}
+			summary.epochs += 1;
+			current_epoch = Some(epoch);
+		}
+
+		record_finalized_block(src, conn, n, h, &hdr, slot, prev_slot)?;
+		prev_slot = Some(slot);
+	}
+	Ok(summary)
+}
+
+/// First block whose Aura slot is at or after `slot` (binary search over `0..=finalized`).
+pub fn first_block_at_or_after_slot<C: ChainSource>(src: &C, slot: u64) -> anyhow::Result<Option<u64>> {
+	let finalized = src.finalized_number()?;
+	let (mut lo, mut hi) = (0u64, finalized + 1);
+	while lo < hi {
+		let mid = lo + (hi - lo) / 2;
+		let mid_slot = src
+			.header_by_number(mid)?
+			.and_then(|(_, hdr)| aura_slot_from_header(&hdr))
+			.unwrap_or(0);
+		if mid_slot < slot {
+			lo = mid + 1;
+		} else {
+			hi = mid;
+		}
+	}
+	Ok((lo <= finalized).then_some(lo))
+}
+
+/// Finalized block range covering epochs `from_epoch..=to_epoch`.
+pub fn epoch_block_range<C: ChainSource>(
+	src: &C,
+	epoch_size: u64,
+	from_epoch: u64,
+	to_epoch: u64,
+) -> anyhow::Result<(u64, u64)> {
+	let from_block = first_block_at_or_after_slot(src, from_epoch * epoch_size)?
+		.ok_or_else(|| anyhow!("epoch {from_epoch} has no finalized blocks yet"))?;
+	let to_block = match first_block_at_or_after_slot(src, (to_epoch + 1) * epoch_size)? {
+		Some(n) => n.saturating_sub(1),
+		None => src.finalized_number()?,
+	};
+	Ok((from_block, to_block))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chain::scripted::ScriptedChain;
+	use crate::store::ensure_db;
+	use rusqlite::params;
+	use substrate_api_client::ac_primitives::sr25519;
+
+	fn key(b: u8) -> sr25519::Public {
+		sr25519::Public::from_raw([b; 32])
+	}
+
+	fn scheduled(conn: &Connection, epoch: u64) -> Vec<(u64, String)> {
+		let mut stmt = conn.prepare("SELECT slot, status FROM blocks WHERE epoch=?1 ORDER BY slot").unwrap();
+		stmt.query_map(params![epoch as i64], |r| Ok((r.get::<_, i64>(0)? as u64, r.get(1)?)))
+			.unwrap()
+			.collect::<Result<_, _>>()
+			.unwrap()
+	}
+
+	#[test]
+	fn authority_set_changing_at_epoch_boundary() {
+		// Epochs of 4 slots; the session rotates in the first block of epoch 1, swapping the two seats.
+		let mut src = ScriptedChain::new(6000, vec![key(1), key(2)]);
+		for slot in 1..=3 {
+			src.push_block(slot);
+		}
+		src.set_authorities(vec![key(2), key(1)]);
+		for slot in 4..=7 {
+			src.push_block(slot);
+		}
+		src.finalize(7);
+		let mut conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+
+		let summary = backfill_blocks(&src, &mut conn, &[[1u8; 32]], 4, 1, 7, |_, _| {}).unwrap();
+		assert_eq!(summary.epochs, 2);
+		assert_eq!(scheduled(&conn, 0), vec![(2, "finality".to_string())]);
+		assert_eq!(scheduled(&conn, 1), vec![(5, "finality".to_string()), (7, "finality".to_string())]);
+		let first: String = conn
+			.query_row("SELECT aura FROM epoch_authorities WHERE epoch=1 AND position=0", [], |r| r.get(0))
+			.unwrap();
+		assert_eq!(first, hex0x(&[2u8; 32]));
+	}
+}
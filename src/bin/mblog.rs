@@ -1,25 +1,49 @@
+use anyhow::anyhow;
 use clap::{Args, Parser, Subcommand};
-use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use std::io::IsTerminal;
-use std::io::Write;
-use std::{path::Path, time::Duration};
-use substrate_api_client::{
-	ac_node_api::storage::GetStorageTypes,
-	ac_node_api::DecodeAsType,
-	ac_primitives::{sr25519, DefaultRuntimeConfig},
-	rpc::TungsteniteRpcClient,
-	Api, GetChainInfo, GetStorage,
+use midnight_blocklog::backfill::{backfill_blocks, epoch_block_range};
+use midnight_blocklog::chain::{
+	aura_slot_from_header, author_has_aura_key, connect, fetch_authorities, resolve_epoch_size, ChainSource,
+	CommitteeSchedule, HeadEvent, NodeApi,
 };
-use substrate_api_client::rpc::Request;
-use anyhow::anyhow;
-use chrono::{FixedOffset, Local, Utc};
-use rusqlite::{params, params_from_iter, Connection};
-use scale_value::{Composite, Primitive, Value as ScaleValue, ValueDef, Variant};
+use midnight_blocklog::debug::{
+	debug_decode_plain_storage, debug_list_storage, debug_read_plain_storage, debug_session_metadata,
+	print_pallets,
+};
+use midnight_blocklog::keystore::{
+	detect_aura_pubkeys_from_keystore, detect_sidechain_pubkey_from_keystore, parse_pubkey_hex,
+};
+use midnight_blocklog::metrics::{serve_metrics, AuthorMetrics, Metrics, SharedMetrics};
+use midnight_blocklog::notify::{Event, EventKind, Notifier, WebhookConfig};
+use midnight_blocklog::registration::{
+	fetch_registration_status, format_ada_from_lovelace, jsonrpc_http_call,
+};
+use midnight_blocklog::remind::{parse_lead_time, Reminders};
+use midnight_blocklog::render::{
+	format_dt, format_rfc3339_in_tz, format_ts, hex0x, hex32, parse_log_time, parse_output_tz, parse_rfc3339_utc,
+	print_kv_table, print_progress, print_table, render_progress_bar, status_tag, ColorMode, Colors, I18n, Lang, OutputTz,
+};
+use midnight_blocklog::schedule::{
+	author_in_authorities, committee_slots, compute_my_slots, epoch_committee, planned_ts_ms, schedule_hash,
+};
+use midnight_blocklog::stats::{
+	committee_stats, compute_stats, quantile, settled_slot_ranges, MemberStats, ProductionStats,
+};
+use midnight_blocklog::store::{
+	db_chain_finalized_blocks, db_chain_produced_counts, db_claim_legacy_rows, db_drop_stale_next_schedule,
+	db_epoch_status_counts, db_fetch_log_rows, db_fetch_schedule_rows, db_finality_lags, db_insert_schedule,
+	db_replace_epoch_authorities, db_replace_epoch_committee, db_upsert_epoch_info, ensure_db, is_store_error,
+	schedule_rows_hash, LogFilter, LogRow, ScheduleRow, COMMITTEE_SOURCE_CURRENT, COMMITTEE_SOURCE_NEXT, LOG_FIELDS,
+	SCHEDULE_SOURCE_AURA,
+};
+use midnight_blocklog::watch::{HeadState, Watcher};
+use rusqlite::Connection;
 use serde_json::Value;
-use sp_core::crypto::{AccountId32, KeyTypeId};
-use sp_runtime::generic::DigestItem;
-use unicode_width::UnicodeWidthStr;
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "mblog", version)]
@@ -29,23 +53,34 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Command {
 	/// Show Aura slot schedule (use --watch to monitor)
 	Block(CommonArgs),
 	/// Show stored blocks from SQLite
 	Log(BlockArgs),
+	/// Rebuild block history for a past block or epoch range (requires an archive node)
+	Backfill(BackfillArgs),
+	/// Production statistics per epoch and overall from SQLite
+	Stats(StatsArgs),
+	/// Committee-wide leaderboard of an epoch (block counts from `chain_blocks`)
+	Committee(CommitteeArgs),
 }
 
 #[derive(Args)]
 struct CommonArgs {
 	    #[arg(long, default_value = "ws://127.0.0.1:9944")]
 	    ws: String,
-	    /// Path to the node's keystore directory. The Aura public key is auto-detected from this.
-	    #[arg(long)]
-	    keystore_path: String,
-		    #[arg(long, default_value_t = 1200)]
-		    epoch_size: u32,
-	/// Output schedule JSON to stdout (requires --current or --next; cannot be used with --watch)
+	    /// Path to a node keystore directory; every Aura public key in it is monitored. Repeat for several validators.
+	    #[arg(long, required_unless_present = "author")]
+	    keystore_path: Vec<String>,
+	    /// Extra Aura public key (hex) to monitor without a keystore. Repeatable.
+	    #[arg(long, value_name = "PUBKEY")]
+	    author: Vec<String>,
+		    /// Slots per epoch; only used when the runtime does not expose `Sidechain.SlotsPerEpoch` (default: 1200)
+		    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+		    epoch_size: Option<u32>,
+	/// Output schedule JSON to stdout (requires --current, --next, --epochs or --all-known; cannot be used with --watch)
 	#[arg(long, conflicts_with = "watch")]
 	output_json: bool,
 	/// Output the current epoch schedule (requires --output-json)
@@ -54,6 +89,20 @@ struct CommonArgs {
 	/// Output the next epoch schedule (requires --output-json)
 	#[arg(long, requires = "output_json", conflicts_with = "current")]
 	next: bool,
+	/// Output one document for the current epoch and the following ones, N epochs in total, as far as the
+	/// chain knows them (requires --output-json)
+	#[arg(
+		long,
+		value_name = "N",
+		requires = "output_json",
+		conflicts_with_all = ["current", "next", "all_known"],
+		value_parser = clap::value_parser!(u32).range(1..)
+	)]
+	epochs: Option<u32>,
+	/// Like --epochs, for every epoch the chain already knows: the current one, plus the next once
+	/// `NextCommittee` is set (requires --output-json)
+	#[arg(long, requires = "output_json", conflicts_with_all = ["current", "next"])]
+	all_known: bool,
 		    /// Output language for fixed messages: ja|en
 		    #[arg(long, value_enum, default_value = "en")]
 		    lang: Lang,
@@ -84,6 +133,40 @@ struct CommonArgs {
 	#[arg(long)]
 	watch: bool,
 
+	/// Serve Prometheus metrics on this address in watch mode (e.g. 127.0.0.1:9615)
+	#[arg(long, requires = "watch", value_name = "ADDR")]
+	metrics_addr: Option<String>,
+
+	/// POST a JSON notification to this URL on schedule, mint, finality, missed/orphaned slots,
+	/// leaving the authority set and the registration turning invalid
+	#[arg(long, value_name = "URL", conflicts_with = "output_json")]
+	webhook_url: Option<String>,
+
+	/// Webhook body template, inline or `@FILE` (placeholders such as {{message}}; default: JSON event)
+	#[arg(long, requires = "webhook_url", value_name = "TEMPLATE")]
+	webhook_template: Option<String>,
+
+	/// Webhook delivery retries (exponential backoff)
+	#[arg(long, default_value_t = 3, value_name = "N")]
+	webhook_retries: u32,
+
+	/// Remind this long before each scheduled slot, e.g. 10m, 1m, 90s (repeatable)
+	#[arg(long = "remind", value_name = "LEAD")]
+	remind: Vec<String>,
+
+	/// Command run (via the shell) for each reminder, with MBLOG_* environment variables
+	#[arg(long, requires = "remind", value_name = "CMD")]
+	remind_command: Option<String>,
+
+	/// Also record every walked block header (all authors) in the `chain_blocks` table
+	#[arg(long, conflicts_with_all = ["no_store", "output_json"])]
+	record_chain: bool,
+
+	/// Max number of blocks to backfill on startup from the cursors stored by the previous run (0 = no backfill;
+	/// --watch only)
+	#[arg(long, default_value_t = 14400)]
+	max_backfill: u64,
+
 	/// Print metadata / storage availability diagnostics for Session-related items
 	#[arg(long, hide = true)]
 	debug_metadata: bool,
@@ -126,979 +209,751 @@ struct BlockArgs {
 	/// SQLite DB path
 	#[arg(long, default_value = "./mblog.db")]
 	db: String,
-	/// Filter by epoch
-	#[arg(long)]
+	/// Filter by epoch (default: latest, unless another range filter is given)
+	#[arg(long, conflicts_with_all = ["from_epoch", "to_epoch"])]
 	epoch: Option<u64>,
+	/// First epoch to show
+	#[arg(long, value_name = "EPOCH")]
+	from_epoch: Option<u64>,
+	/// Last epoch to show
+	#[arg(long, value_name = "EPOCH")]
+	to_epoch: Option<u64>,
+	/// Only blocks planned at or after this time: RFC 3339, YYYY-MM-DD (UTC), or relative (e.g. 7d, 12h)
+	#[arg(long, value_name = "TIME")]
+	since: Option<String>,
+	/// Only blocks planned at or before this time (same formats as --since)
+	#[arg(long, value_name = "TIME")]
+	until: Option<String>,
+	/// Only blocks with these statuses (repeatable or comma-separated)
+	#[arg(
+		long,
+		value_delimiter = ',',
+		value_parser = ["schedule", "mint", "finality", "missed", "orphaned"]
+	)]
+	status: Vec<String>,
+	/// Only the last N matching blocks
+	#[arg(long, value_name = "N")]
+	last: Option<u64>,
+	/// Only these slots (repeatable or comma-separated)
+	#[arg(long, value_delimiter = ',')]
+	slot: Vec<u64>,
+	/// Only show blocks of this Aura public key (hex)
+	#[arg(long, value_name = "PUBKEY")]
+	author: Option<String>,
 	/// Display timezone for scheduled time (same format as `mblog slot --tz`)
 	#[arg(long, default_value = "UTC")]
 	tz: String,
 	/// Output language for fixed messages: ja|en
 	#[arg(long, value_enum, default_value = "en")]
 	lang: Lang,
+	/// Output format: table|json|ndjson|csv (json/ndjson/csv emit every stored column)
+	#[arg(long, value_enum, default_value = "table")]
+	format: LogFormat,
 }
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
-enum ColorMode {
-	Auto,
-	Always,
-	Never,
-}
-
-#[derive(clap::ValueEnum, Clone, Copy, Debug)]
-enum Lang {
-	Ja,
-	En,
-}
-
-struct I18n {
-	lang: Lang,
-}
-
-impl I18n {
-	fn new(lang: Lang) -> Self {
-		Self { lang }
-	}
-
-	fn pick<'a>(&self, en: &'a str, ja: &'a str) -> &'a str {
-		match self.lang {
-			Lang::En => en,
-			Lang::Ja => ja,
-		}
-	}
-}
-
-struct Colors {
-	enabled: bool,
-}
-
-impl Colors {
-	fn new(mode: ColorMode) -> Self {
-		let enabled = match mode {
-			ColorMode::Always => true,
-			ColorMode::Never => false,
-			ColorMode::Auto => std::io::stdout().is_terminal(),
+enum LogFormat {
+	Table,
+	Json,
+	Ndjson,
+	Csv,
+}
+
+/// One monitored Aura key.
+struct MonitoredKey {
+	author_hex: String,
+	author_bytes: [u8; 32],
+	/// Sidechain key from the same keystore, for the registration check.
+	sidechain_pubkey: Option<String>,
+	/// Detected from a `--keystore-path` (so the node should hold it), as opposed to `--author`.
+	from_keystore: bool,
+}
+
+/// Per-key display state of the watch loop.
+#[derive(Default)]
+struct KeyView {
+	present: Option<bool>,
+	my_slots: Vec<u64>,
+	schedule_hash: Option<[u8; 32]>,
+	view_hash: Option<[u8; 32]>,
+	/// Scheduled slots not yet final (`schedule`/`mint`), diffed against the DB for webhook events.
+	tracked: BTreeMap<u64, String>,
+	/// `(slot, planned_time_utc)` of the displayed schedule, for `--remind`.
+	planned: Vec<(u64, String)>,
+	/// `(slot, planned_time_utc)` of the next epoch from `NextCommittee`, once it is readable.
+	next_planned: Vec<(u64, String)>,
+}
+
+/// Aura keys from every `--keystore-path` plus the explicit `--author` keys, deduplicated.
+/// The sidechain key (registration check) is only paired when a keystore holds a single Aura key.
+fn collect_monitored_keys(
+	keystore_paths: &[String],
+	authors: &[String],
+	with_sidechain: bool,
+) -> anyhow::Result<Vec<MonitoredKey>> {
+	let mut keys: Vec<MonitoredKey> = Vec::new();
+	for path in keystore_paths {
+		let path = Path::new(path);
+		let found = detect_aura_pubkeys_from_keystore(path)?;
+		let sidechain_pubkey = if with_sidechain && found.len() == 1 {
+			Some(detect_sidechain_pubkey_from_keystore(path)?)
+		} else {
+			None
 		};
-		Self { enabled }
-	}
-
-	fn wrap(&self, s: impl AsRef<str>, code: &str) -> String {
-		let s = s.as_ref();
-		if !self.enabled {
-			return s.to_string();
-		}
-		format!("\x1b[{code}m{s}\x1b[0m")
-	}
-
-	fn epoch(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "36") // cyan
-	}
-	fn range(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "33") // yellow
-	}
-	fn author(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "35") // magenta
-	}
-	fn slot(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "34") // blue
-	}
-	fn time(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "32") // green
-	}
-	fn dim(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "90") // bright black
-	}
-
-	fn error(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "31") // red
-	}
-
-	fn ok(&self, v: impl AsRef<str>) -> String {
-		self.wrap(v, "32") // green
-	}
-}
-
-fn render_progress_bar(percent: u8, width: usize) -> String {
-	let percent = percent.min(100) as usize;
-	let filled = (percent * width) / 100;
-	let empty = width.saturating_sub(filled);
-	format!("[{}{}]", "=".repeat(filled), " ".repeat(empty))
-}
-
-fn print_progress(
-	is_tty: bool,
-	colors: &Colors,
-	label: &str,
-	percent: u8,
-	current_slot: u64,
-	end_slot: u64,
-) {
-	let bar = render_progress_bar(percent, 30);
-	let line = format!(
-		"{label} {} {}% (slot {}/{})",
-		colors.dim(bar),
-		colors.epoch(percent.to_string()),
-		colors.range(current_slot.to_string()),
-		colors.range(end_slot.to_string())
-	);
-
-	if is_tty {
-		print!("\r\x1b[2K{line}");
-		let _ = std::io::stdout().flush();
-	} else {
-		println!("{line}");
-	}
-}
-
-enum OutputTz {
-	Utc,
-	Local,
-	/// Local time, but forced via TZ environment (Unix).
-	ForcedLocal,
-	Fixed(FixedOffset),
-}
-
-fn parse_output_tz(s: &str) -> anyhow::Result<OutputTz> {
-	let s = s.trim();
-	if s.eq_ignore_ascii_case("utc") {
-		return Ok(OutputTz::Utc);
-	}
-	if s.eq_ignore_ascii_case("local") {
-		return Ok(OutputTz::Local);
-	}
-	// Fixed offset: ±HH:MM
-	let bytes = s.as_bytes();
-	if bytes.len() == 6 && (bytes[0] == b'+' || bytes[0] == b'-') && bytes[3] == b':' {
-		let sign = if bytes[0] == b'+' { 1 } else { -1 };
-		let hh: i32 = s[1..3].parse()?;
-		let mm: i32 = s[4..6].parse()?;
-		if hh > 23 || mm > 59 {
-			return Err(anyhow!("invalid offset '{s}'"));
+		for author_hex in found {
+			let author_bytes = parse_pubkey_hex(&author_hex)
+				.map_err(|e| anyhow!("invalid aura pubkey from keystore: {e}"))?;
+			keys.push(MonitoredKey {
+				author_hex,
+				author_bytes,
+				sidechain_pubkey: sidechain_pubkey.clone(),
+				from_keystore: true,
+			});
 		}
-		let secs = sign * (hh * 3600 + mm * 60);
-		let off = FixedOffset::east_opt(secs).ok_or_else(|| anyhow!("invalid offset '{s}'"))?;
-		return Ok(OutputTz::Fixed(off));
 	}
-
-	// IANA timezone like "Asia/Dubai"
-	if s.contains('/') {
-		#[cfg(unix)]
-		{
-			unsafe {
-				std::env::set_var("TZ", s);
-				tzset();
-			}
-			return Ok(OutputTz::ForcedLocal);
-		}
-		#[cfg(not(unix))]
-		{
-			return Err(anyhow!(
-				"--tz '{s}' looks like an IANA zone, but this mode is only supported on Unix"
-			));
-		}
+	for author in authors {
+		let author_bytes = parse_pubkey_hex(author).map_err(|e| anyhow!("invalid --author '{author}': {e}"))?;
+		keys.push(MonitoredKey {
+			author_hex: hex0x(&author_bytes),
+			author_bytes,
+			sidechain_pubkey: None,
+			from_keystore: false,
+		});
 	}
-
-	Err(anyhow!(
-		"invalid --tz '{s}' (use UTC | local | +HH:MM | -HH:MM | Area/City)"
-	))
-}
-
-fn format_ts(ts_ms: i64, tz: &OutputTz) -> String {
-	let dt_utc = chrono::DateTime::<Utc>::from_timestamp_millis(ts_ms).unwrap_or_else(|| Utc::now());
-	format_dt(dt_utc, tz)
-}
-
-fn format_dt(dt_utc: chrono::DateTime<chrono::Utc>, tz: &OutputTz) -> String {
-	match tz {
-		OutputTz::Utc => dt_utc.to_rfc3339(),
-		OutputTz::Local => dt_utc.with_timezone(&Local).to_rfc3339(),
-		OutputTz::ForcedLocal => dt_utc.with_timezone(&Local).to_rfc3339(),
-		OutputTz::Fixed(off) => dt_utc.with_timezone(off).to_rfc3339(),
+	let mut seen = std::collections::HashSet::new();
+	keys.retain(|k| seen.insert(k.author_bytes));
+	if keys.is_empty() {
+		return Err(anyhow!("no Aura key to monitor (use --keystore-path or --author)"));
 	}
+	Ok(keys)
 }
 
-fn parse_rfc3339_utc(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-	let dt = chrono::DateTime::parse_from_rfc3339(s).ok()?;
-	Some(dt.with_timezone(&Utc))
-}
-
-#[cfg(unix)]
-unsafe extern "C" {
-	fn tzset();
-}
-
-fn hex32(bytes: [u8; 32]) -> String {
-	format!("0x{}", hex::encode(bytes))
-}
-
-fn ensure_db(conn: &Connection) -> anyhow::Result<()> {
-	conn.execute_batch(
-		r#"
-CREATE TABLE IF NOT EXISTS epoch_info (
-  epoch INTEGER PRIMARY KEY,
-  start_slot INTEGER NOT NULL,
-  end_slot INTEGER NOT NULL,
-  authority_set_hash TEXT NOT NULL,
-  authority_set_len INTEGER NOT NULL,
-  created_at_utc TEXT NOT NULL
-);
-
-CREATE TABLE IF NOT EXISTS blocks (
-  slot INTEGER PRIMARY KEY,
-  epoch INTEGER NOT NULL,
-  planned_time_utc TEXT NOT NULL,
-  block_number INTEGER,
-  block_hash TEXT,
-  produced_time_utc TEXT,
-  status TEXT NOT NULL
-);
-
-CREATE INDEX IF NOT EXISTS idx_blocks_epoch ON blocks(epoch);
-"#,
-	)?;
-	Ok(())
-}
-
-fn db_upsert_epoch_info(
-	conn: &Connection,
-	epoch: u64,
-	start_slot: u64,
-	end_slot: u64,
-	authority_set_hash: &str,
-	authority_set_len: usize,
-) -> anyhow::Result<()> {
-	let now_utc = chrono::Utc::now().to_rfc3339();
-	conn.execute(
-		r#"
-INSERT INTO epoch_info(epoch, start_slot, end_slot, authority_set_hash, authority_set_len, created_at_utc)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-ON CONFLICT(epoch) DO UPDATE SET
-  start_slot=excluded.start_slot,
-  end_slot=excluded.end_slot,
-  authority_set_hash=excluded.authority_set_hash,
-  authority_set_len=excluded.authority_set_len,
-  created_at_utc=excluded.created_at_utc
-"#,
-		params![
-			epoch as i64,
-			start_slot as i64,
-			end_slot as i64,
-			authority_set_hash,
-			authority_set_len as i64,
-			now_utc
-		],
-	)?;
-	Ok(())
-}
-
-fn db_insert_schedule(
-	conn: &mut Connection,
-	epoch: u64,
+/// Schedule rows for `slots` as stored in SQLite, falling back to the freshly planned times.
+fn load_schedule_rows(
+	conn: Option<&Connection>,
+	slots: &[u64],
 	planned: &[(u64, String)],
-) -> anyhow::Result<()> {
-	let tx = conn.transaction()?;
-	{
-		let mut stmt = tx.prepare(
-			r#"
-INSERT INTO blocks(slot, epoch, planned_time_utc, status)
-VALUES (?1, ?2, ?3, 'schedule')
-ON CONFLICT(slot) DO UPDATE SET
-  epoch=excluded.epoch,
-  planned_time_utc=excluded.planned_time_utc,
-  status=CASE
-    WHEN blocks.status='finality' THEN blocks.status
-    ELSE excluded.status
-  END
-"#,
-		)?;
-		for (slot, planned_time_utc) in planned {
-			stmt.execute(params![*slot as i64, epoch as i64, planned_time_utc])?;
+) -> anyhow::Result<Vec<ScheduleRow>> {
+	let planned_by_slot: HashMap<u64, String> = planned.iter().map(|(s, t)| (*s, t.clone())).collect();
+	let mut by_slot: HashMap<u64, ScheduleRow> = HashMap::new();
+	if let Some(c) = conn {
+		for r in db_fetch_schedule_rows(c, slots)? {
+			by_slot.insert(r.slot, r);
 		}
 	}
-	tx.commit()?;
-	Ok(())
-}
-
-fn db_update_block_status(
-	conn: &Connection,
-	slot: u64,
-	block_number: u64,
-	block_hash: &str,
-	produced_time_utc: &str,
-	status: &str,
-) -> anyhow::Result<()> {
-	conn.execute(
-		r#"
-UPDATE blocks
-SET block_number=?2, block_hash=?3, produced_time_utc=?4, status=?5
-WHERE slot=?1
-  AND (
-    (?5='mint' AND status='schedule') OR
-    (?5='finality' AND status IN ('schedule','mint'))
-  )
-"#,
-		params![
-			slot as i64,
-			block_number as i64,
-			block_hash,
-			produced_time_utc,
-			status
-		],
-	)?;
-	Ok(())
-}
-
-fn db_upsert_minted_block(
-	conn: &Connection,
-	slot: u64,
-	epoch: u64,
-	block_number: u64,
-	block_hash: &str,
-	produced_time_utc: &str,
-) -> anyhow::Result<()> {
-	// Unlike finality, mint is rare and only relevant to "our" blocks.
-	// If the schedule row is missing for any reason, we still want to record mint.
-	conn.execute(
-		r#"
-INSERT INTO blocks(slot, epoch, planned_time_utc, block_number, block_hash, produced_time_utc, status)
-VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'mint')
-ON CONFLICT(slot) DO UPDATE SET
-  block_number=excluded.block_number,
-  block_hash=excluded.block_hash,
-  produced_time_utc=excluded.produced_time_utc,
-  status=CASE
-    WHEN blocks.status='finality' THEN blocks.status
-    WHEN blocks.status='schedule' THEN 'mint'
-    ELSE blocks.status
-  END
-"#,
-		params![
-			slot as i64,
-			epoch as i64,
-			produced_time_utc,
-			block_number as i64,
-			block_hash,
-			produced_time_utc
-		],
-	)?;
-	Ok(())
-}
-
-#[derive(Clone)]
-struct ScheduleRow {
-	slot: u64,
-	planned_time_utc: String,
-	status: String,
-	block_number: Option<u64>,
-	block_hash: Option<String>,
-	produced_time_utc: Option<String>,
+	Ok(slots
+		.iter()
+		.map(|slot| {
+			by_slot.remove(slot).unwrap_or_else(|| ScheduleRow {
+				slot: *slot,
+				planned_time_utc: planned_by_slot.get(slot).cloned().unwrap_or_else(|| "-".to_string()),
+				status: "schedule".to_string(),
+				block_number: None,
+				block_hash: None,
+				produced_time_utc: None,
+			})
+		})
+		.collect())
 }
 
-fn db_fetch_schedule_rows(conn: &Connection, slots: &[u64]) -> anyhow::Result<Vec<ScheduleRow>> {
-	if slots.is_empty() {
-		return Ok(Vec::new());
-	}
-
-	let placeholders = std::iter::repeat("?")
-		.take(slots.len())
-		.collect::<Vec<_>>()
-		.join(",");
-	let sql = format!(
-		"SELECT slot, planned_time_utc, status, block_number, block_hash, produced_time_utc \
-		 FROM blocks WHERE slot IN ({placeholders}) ORDER BY slot ASC"
+fn print_schedule_rows(
+	i18n: &I18n,
+	colors: &Colors,
+	out_tz: &OutputTz,
+	utc_tz: &OutputTz,
+	epoch_idx: u64,
+	schedule_rows: &[ScheduleRow],
+) {
+	println!(
+		"{}: {}",
+		i18n.pick("Current epoch Schedule", "現在のエポックのスケジュール"),
+		colors.epoch(epoch_idx.to_string())
 	);
+	println!("-------------------------");
+	for (idx, row) in schedule_rows.iter().enumerate() {
+		let dt_utc = parse_rfc3339_utc(&row.planned_time_utc);
+		let out_ts = dt_utc
+			.map(|dt| colors.time(format_dt(dt, out_tz)))
+			.unwrap_or_else(|| "-".to_string());
+		let utc_ts = dt_utc
+			.map(|dt| format_dt(dt, utc_tz))
+			.unwrap_or_else(|| "-".to_string());
 
-	let mut stmt = conn.prepare(&sql)?;
-	let mut rows = stmt.query(params_from_iter(slots.iter().map(|s| *s as i64)))?;
-
-	let mut out: Vec<ScheduleRow> = Vec::new();
-	while let Some(row) = rows.next()? {
-		let slot: i64 = row.get(0)?;
-		let planned_time_utc: String = row.get(1)?;
-		let status: String = row.get(2)?;
-		let block_number: Option<i64> = row.get(3)?;
-		let block_hash: Option<String> = row.get(4)?;
-		let produced_time_utc: Option<String> = row.get(5)?;
-		out.push(ScheduleRow {
-			slot: slot as u64,
-			planned_time_utc,
+		let status = status_tag(colors, &row.status);
+		println!(
+			"#{idx1} slot {}: {} (UTC {}){}",
+			colors.slot(row.slot.to_string()),
+			out_ts,
+			colors.dim(utc_ts),
 			status,
-			block_number: block_number.map(|n| n as u64),
-			block_hash,
-			produced_time_utc,
-		});
-	}
-	Ok(out)
-}
-
-fn schedule_rows_hash(rows: &[ScheduleRow]) -> [u8; 32] {
-	let mut hasher = Sha256::new();
-	for r in rows {
-		hasher.update(r.slot.to_le_bytes());
-		hasher.update(r.planned_time_utc.as_bytes());
-		hasher.update(b"\0");
-		hasher.update(r.status.as_bytes());
-		hasher.update(b"\0");
-		if let Some(n) = r.block_number {
-			hasher.update(n.to_le_bytes());
-		}
-		hasher.update(b"\0");
-		if let Some(ref h) = r.block_hash {
-			hasher.update(h.as_bytes());
-		}
-		hasher.update(b"\0");
-		if let Some(ref t) = r.produced_time_utc {
-			hasher.update(t.as_bytes());
-		}
-		hasher.update(b"\0");
+			idx1 = idx + 1
+		);
 	}
-	hasher.finalize().into()
+	println!("{}={}", i18n.pick("Total", "合計"), schedule_rows.len());
 }
 
-fn aura_slot_from_header(
-	header: &<DefaultRuntimeConfig as substrate_api_client::ac_primitives::config::Config>::Header,
-) -> Option<u64> {
-	for log in &header.digest.logs {
-		if let DigestItem::PreRuntime(engine_id, data) = log {
-			if engine_id != b"aura" {
-				continue;
-			}
-			let raw: [u8; 8] = data.get(0..8)?.try_into().ok()?;
-			return Some(u64::from_le_bytes(raw));
-		}
+/// Webhook message prefix for a slot status event.
+fn slot_event_label(kind: EventKind, i18n: &I18n) -> &'static str {
+	match kind {
+		EventKind::Mint => i18n.pick("Block minted", "ブロック生成"),
+		EventKind::Finality => i18n.pick("Block finalized", "ブロック確定"),
+		EventKind::Missed => i18n.pick("Slot missed", "スロット未生成"),
+		EventKind::Orphaned => i18n.pick("Block orphaned", "ブロック孤立"),
+		_ => kind.as_str(),
 	}
-	None
 }
 
-fn authorities_at(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	at_hash: sp_core::H256,
-) -> anyhow::Result<Vec<sr25519::Public>> {
-	let res: Option<Vec<sr25519::Public>> = api
-		.get_storage("Aura", "Authorities", Some(at_hash))
-		.map_err(|e| anyhow!("{e:?}"))?;
-	Ok(res.unwrap_or_default())
+/// Planned slots of every monitored key.
+fn reminder_slots(views: &[KeyView]) -> Vec<(u64, String)> {
+	views.iter().flat_map(|v| v.planned.iter().chain(&v.next_planned).cloned()).collect()
 }
 
-fn scan_new_finalized_blocks(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	conn: Option<&Connection>,
-	last_finalized_number: &mut u64,
-) -> anyhow::Result<bool> {
-	let Some(finalized_hash) = api
-		.get_finalized_head()
-		.map_err(|e| anyhow!("{e:?}"))?
-	else {
-		return Ok(false);
-	};
-	let Some(finalized_header) = api
-		.get_header(Some(finalized_hash))
-		.map_err(|e| anyhow!("{e:?}"))?
-	else {
-		return Ok(false);
-	};
-
-	let finalized_number: u64 = finalized_header.number.into();
-	if finalized_number <= *last_finalized_number {
-		return Ok(false);
-	}
-
-	// If we don't store anything, just advance the cursor to avoid repeated scans.
-	let Some(conn) = conn else {
-		*last_finalized_number = finalized_number;
-		return Ok(true);
-	};
-
-	for n in (*last_finalized_number + 1)..=finalized_number {
-		let bn_u32: u32 = n
-			.try_into()
-			.map_err(|_| anyhow!("finalized block number {n} does not fit u32"))?;
-		let Some(h) = api.get_block_hash(Some(bn_u32)).map_err(|e| anyhow!("{e:?}"))? else {
-			continue;
-		};
-		let Some(hdr) = api.get_header(Some(h)).map_err(|e| anyhow!("{e:?}"))? else {
-			continue;
-		};
-		let Some(slot) = aura_slot_from_header(&hdr) else {
+/// Print the due pre-slot reminders and forward them to `--remind-command` and the webhook.
+#[allow(clippy::too_many_arguments)]
+fn fire_reminders(
+	reminders: &mut Reminders,
+	keys: &[MonitoredKey],
+	views: &[KeyView],
+	remind_command: Option<&str>,
+	notifier: Option<&Notifier>,
+	epoch_size: u64,
+	out_tz: &OutputTz,
+	i18n: &I18n,
+	colors: &Colors,
+) {
+	for due in reminders.due(&reminder_slots(views), chrono::Utc::now()) {
+		let Some(key) = keys
+			.iter()
+			.zip(views)
+			.find(|(_, v)| v.planned.iter().chain(&v.next_planned).any(|(s, _)| *s == due.slot))
+			.map(|(k, _)| k)
+		else {
 			continue;
 		};
-		let block_hash_str = format!("{h:?}");
-		let produced_time_utc = block_time_utc(api, h);
-		db_update_block_status(conn, slot, n, &block_hash_str, &produced_time_utc, "finality")?;
-	}
-
-	*last_finalized_number = finalized_number;
-	Ok(true)
-}
-
-fn block_time_utc(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	hash: sp_core::H256,
-) -> String {
-	let ts_ms: Option<u64> = api
-		.get_storage("Timestamp", "Now", Some(hash))
-		.map_err(|e| anyhow!("{e:?}"))
-		.ok()
-		.flatten();
-	match ts_ms {
-		Some(ms) => chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ms as i64)
-			.unwrap()
-			.to_rfc3339(),
-		None => chrono::Utc::now().to_rfc3339(),
-	}
-}
-
-fn author_has_aura_key(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	public_key_hex: &str,
-) -> anyhow::Result<bool> {
-	let mut params = substrate_api_client::ac_primitives::RpcParams::new();
-	params
-		.insert(public_key_hex)
-		.map_err(|e| anyhow!("failed to build RPC params: {e}"))?;
-	params
-		.insert("aura")
-		.map_err(|e| anyhow!("failed to build RPC params: {e}"))?;
-
-	api.client()
-		.request("author_hasKey", params)
-		.map_err(|e| anyhow!("author_hasKey RPC failed: {e:?}"))
-}
-
-fn parse_pubkey_hex(s: &str) -> anyhow::Result<[u8; 32]> {
-	let hex_str = s.trim_start_matches("0x");
-	let bytes = hex::decode(hex_str)?;
-	let len = bytes.len();
-	let arr: [u8; 32] = bytes
-		.as_slice()
-		.try_into()
-		.map_err(|_| anyhow::anyhow!("expected 32-byte hex, got {} bytes", len))?;
-	Ok(arr)
-}
-
-fn detect_aura_pubkey_from_keystore(keystore_path: &Path) -> anyhow::Result<String> {
-	let mut found: Vec<String> = Vec::new();
+		let epoch = due.slot / epoch_size;
+		let planned_local = parse_rfc3339_utc(&due.planned_time_utc)
+			.map(|t| format_dt(t, out_tz))
+			.unwrap_or_else(|| due.planned_time_utc.clone());
+		let message = format!(
+			"{}: slot {} at {planned_local} ({}s)",
+			i18n.pick("Block production soon", "まもなくブロック生成"),
+			due.slot,
+			due.seconds_left
+		);
+		println!();
+		println!(
+			"{}: {} {} {}",
+			colors.ok(i18n.pick("reminder", "リマインダー")),
+			colors.slot(format!("slot {}", due.slot)),
+			colors.time(&planned_local),
+			colors.dim(format!("({}s, author {})", due.seconds_left, key.author_hex))
+		);
 
-	for entry in std::fs::read_dir(keystore_path).map_err(|e| {
-		anyhow!("failed to read --keystore-path '{}': {e}", keystore_path.display())
-	})? {
-		let entry = entry.map_err(|e| anyhow!("failed to read directory entry: {e}"))?;
-		let file_type = entry.file_type().map_err(|e| anyhow!("failed to stat entry: {e}"))?;
-		if !file_type.is_file() {
-			continue;
-		}
-		let name_os = entry.file_name();
-		let Some(name) = name_os.to_str() else {
-			continue;
-		};
-		let mut hex_name = name.trim().to_ascii_lowercase();
-		if let Some(rest) = hex_name.strip_prefix("0x") {
-			hex_name = rest.to_string();
-		}
-		// Substrate keystore filenames are typically: <4-byte key type><32-byte pubkey> as hex.
-		// For Aura, key type is "aura" => 0x61757261.
-		if hex_name.len() == 72 && hex_name.starts_with("61757261") {
-			let pub_hex = &hex_name[8..];
-			if pub_hex.chars().all(|c| c.is_ascii_hexdigit()) {
-				found.push(format!("0x{pub_hex}"));
+		if let Some(cmd) = remind_command {
+			let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+			let spawned = std::process::Command::new(shell)
+				.arg(flag)
+				.arg(cmd)
+				.env("MBLOG_EVENT", "reminder")
+				.env("MBLOG_AUTHOR", &key.author_hex)
+				.env("MBLOG_EPOCH", epoch.to_string())
+				.env("MBLOG_SLOT", due.slot.to_string())
+				.env("MBLOG_PLANNED_TIME_UTC", &due.planned_time_utc)
+				.env("MBLOG_LEAD_SECONDS", due.lead_secs.to_string())
+				.env("MBLOG_SECONDS_LEFT", due.seconds_left.to_string())
+				.env("MBLOG_MESSAGE", &message)
+				.spawn();
+			match spawned {
+				// Reap the child without blocking the watch loop.
+				Ok(mut child) => {
+					std::thread::spawn(move || child.wait());
+				}
+				Err(e) => eprintln!(
+					"{}: {}",
+					colors.error(i18n.pick("--remind-command failed", "--remind-command の実行に失敗しました")),
+					colors.dim(e.to_string())
+				),
 			}
 		}
-	}
-
-	found.sort();
-	found.dedup();
-
-	match found.len() {
-		0 => Err(anyhow!(
-			"no Aura key found in keystore '{}': expected a file named like 61757261<pubkey32bytes> (hex)",
-			keystore_path.display()
-		)),
-		1 => Ok(found.remove(0)),
-		_ => Err(anyhow!(
-			"multiple Aura keys found in keystore '{}': {:?}. Keep only one Aura key, or use a dedicated keystore path.",
-			keystore_path.display(),
-			found
-		)),
-	}
-}
-
-fn detect_sidechain_pubkey_from_keystore(keystore_path: &Path) -> anyhow::Result<String> {
-	let mut found: Vec<String> = Vec::new();
 
-	for entry in std::fs::read_dir(keystore_path).map_err(|e| {
-		anyhow!(
-			"failed to read --keystore-path '{}' for sidechain key detection: {e}",
-			keystore_path.display()
-		)
-	})? {
-		let entry = entry.map_err(|e| anyhow!("failed to read directory entry: {e}"))?;
-		let file_type = entry.file_type().map_err(|e| anyhow!("failed to stat entry: {e}"))?;
-		if !file_type.is_file() {
-			continue;
+		if let Some(n) = notifier {
+			let mut event = Event::new(EventKind::Reminder, &key.author_hex, epoch, message);
+			event.slot = Some(due.slot);
+			n.notify(event);
 		}
-		let name_os = entry.file_name();
-		let Some(name) = name_os.to_str() else {
-			continue;
-		};
-		let mut hex_name = name.trim().to_ascii_lowercase();
-		if let Some(rest) = hex_name.strip_prefix("0x") {
-			hex_name = rest.to_string();
-		}
-
-		// Expect: <4-byte key type><33-byte compressed pubkey> as hex.
-		// Many sidechain keys are compressed secp256k1 (33 bytes) starting with 02/03.
-		if hex_name.len() != 74 {
-			continue;
-		}
-		let pub_hex = &hex_name[8..];
-		if !pub_hex.chars().all(|c| c.is_ascii_hexdigit()) {
-			continue;
-		}
-		if !(pub_hex.starts_with("02") || pub_hex.starts_with("03")) {
-			continue;
-		}
-		found.push(format!("0x{pub_hex}"));
-	}
-
-	found.sort();
-	found.dedup();
-
-	match found.len() {
-		0 => Err(anyhow!(
-			"no sidechain public key found in keystore '{}': expected a file named like <keytype><33-byte pubkey> (hex, starts with 02/03)",
-			keystore_path.display()
-		)),
-		1 => Ok(found.remove(0)),
-		_ => Err(anyhow!(
-			"multiple sidechain public keys found in keystore '{}': {:?}. Keep only one sidechain key, or use a dedicated keystore path.",
-			keystore_path.display(),
-			found
-		)),
 	}
 }
 
-fn fetch_authorities(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-) -> anyhow::Result<Vec<sr25519::Public>> {
-	let res: Option<Vec<sr25519::Public>> = api
-		.get_storage("Aura", "Authorities", None)
-		.map_err(|e| anyhow!("{e:?}"))?;
-	Ok(res.unwrap_or_default())
-}
-
-fn format_meta_check(res: Result<(), anyhow::Error>) -> String {
-	match res {
-		Ok(()) => "ok".to_string(),
-		Err(e) => format!("err: {e}"),
-	}
-}
-
-fn hex_bytes_abbrev(bytes: &[u8], max_hex_chars: usize) -> String {
-	let h = hex::encode(bytes);
-	if h.len() <= max_hex_chars {
-		return format!("0x{h}");
+/// Report slots where the committee schedule and the Aura round-robin disagree.
+fn report_schedule_divergence(committee_slots: &[u64], aura_slots: &[u64], i18n: &I18n, colors: &Colors) {
+	if committee_slots == aura_slots {
+		return;
 	}
-	format!("0x{}...{}", &h[..max_hex_chars], &h[h.len().saturating_sub(32)..])
+	let only_committee: Vec<u64> = committee_slots.iter().filter(|s| !aura_slots.contains(s)).copied().collect();
+	let only_aura: Vec<u64> = aura_slots.iter().filter(|s| !committee_slots.contains(s)).copied().collect();
+	eprintln!(
+		"{}",
+		colors.error(format!(
+			"{}: committee={} aura={} / {}: {only_committee:?} / {}: {only_aura:?}",
+			i18n.pick(
+				"WARNING: CurrentCommittee schedule differs from the Aura round-robin",
+				"警告: CurrentCommittee のスケジュールが Aura のラウンドロビンと一致しません"
+			),
+			committee_slots.len(),
+			aura_slots.len(),
+			i18n.pick("only in committee", "委員会のみ"),
+			i18n.pick("only in Aura", "Auraのみ")
+		))
+	);
 }
 
-fn status_tag(colors: &Colors, status: &str) -> String {
-	match status {
-		"finality" => format!(" {}", colors.ok("finality ✅")),
-		"mint" => format!(" {}", colors.range("mint🆕")),
-		_ => format!(" {}", colors.dim("schedule ⏰")),
+/// Report loudly when the runtime's current epoch differs from the one derived from the slot.
+fn warn_on_epoch_mismatch<C: ChainSource>(src: &C, computed_epoch: u64, i18n: &I18n, colors: &Colors) {
+	if let Ok(Some(rt)) = src.current_epoch()
+		&& rt != computed_epoch
+	{
+		eprintln!(
+			"{}",
+			colors.error(format!(
+				"{}: slot/epoch_size={computed_epoch}, runtime={rt}",
+				i18n.pick(
+					"WARNING: epoch mismatch (check --epoch-size)",
+					"警告: エポック番号が一致しません (--epoch-size を確認してください)"
+				)
+			))
+		);
 	}
 }
 
-fn extract_plain_type_id(ty_dbg: &str) -> Option<u32> {
-	let pat = "Plain(UntrackedSymbol { id: ";
-	let Some(rest) = ty_dbg.strip_prefix(pat) else { return None };
-	let n: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
-	n.parse::<u32>().ok()
+#[derive(Args)]
+struct BackfillArgs {
+	#[arg(long, default_value = "ws://127.0.0.1:9944")]
+	ws: String,
+	/// Path to a node keystore directory; every Aura public key in it is backfilled. Repeatable.
+	#[arg(long, required_unless_present = "author")]
+	keystore_path: Vec<String>,
+	/// Extra Aura public key (hex) to backfill without a keystore. Repeatable.
+	#[arg(long, value_name = "PUBKEY")]
+	author: Vec<String>,
+	/// Slots per epoch; only used when the runtime does not expose `Sidechain.SlotsPerEpoch` (default: 1200)
+	#[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+	epoch_size: Option<u32>,
+	/// SQLite DB path
+	#[arg(long, default_value = "./mblog.db")]
+	db: String,
+	/// First block to backfill
+	#[arg(long, required_unless_present = "from_epoch", conflicts_with_all = ["from_epoch", "to_epoch"])]
+	from_block: Option<u64>,
+	/// Last block to backfill (default: finalized head)
+	#[arg(long, requires = "from_block")]
+	to_block: Option<u64>,
+	/// First epoch to backfill
+	#[arg(long)]
+	from_epoch: Option<u64>,
+	/// Last epoch to backfill (default: --from-epoch)
+	#[arg(long, requires = "from_epoch")]
+	to_epoch: Option<u64>,
+	/// Output language for fixed messages: ja|en
+	#[arg(long, value_enum, default_value = "en")]
+	lang: Lang,
+	/// Colorize output: auto|always|never
+	#[arg(long, value_enum, default_value = "auto")]
+	color: ColorMode,
 }
 
-fn print_pallets(api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>, filter: Option<&str>) {
-	let filter_lc = filter.map(|s| s.to_lowercase());
-	for p in api.metadata().pallets() {
-		let name = p.name();
-		if let Some(ref f) = filter_lc {
-			if !name.to_lowercase().contains(f) {
-				continue;
+fn run_backfill(args: BackfillArgs) -> anyhow::Result<()> {
+	let i18n = I18n::new(args.lang);
+	let colors = Colors::new(args.color);
+	let is_tty = std::io::stdout().is_terminal();
+	let keys = collect_monitored_keys(&args.keystore_path, &args.author, false)?;
+	let api = connect(&args.ws)?;
+	let epoch_size = resolve_epoch_size(&api, args.epoch_size, &i18n, &colors);
+	let (from_block, to_block) = match (args.from_block, args.from_epoch) {
+		(Some(from), _) => (from, args.to_block.unwrap_or(u64::MAX)),
+		(None, Some(from_epoch)) => {
+			let to_epoch = args.to_epoch.unwrap_or(from_epoch);
+			if to_epoch < from_epoch {
+				return Err(anyhow!("--to-epoch must not be before --from-epoch"));
 			}
+			epoch_block_range(&api, epoch_size, from_epoch, to_epoch)?
 		}
-		let storage_len = p.storage().len();
-		println!("{name} (index={}, storage={storage_len})", p.index());
-	}
-}
-
-fn debug_read_plain_storage(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	pallet_name: &str,
-	item_name: &str,
-) {
-	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
-		eprintln!("debug-storage: pallet not found: {pallet_name}");
-		return;
-	};
-	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
-		eprintln!("debug-storage: storage item not found: {pallet_name}.{item_name}");
-		return;
+		(None, None) => return Err(anyhow!("backfill requires --from-block or --from-epoch")),
 	};
 
-	let Ok(val) = entry.get_value(pallet_name) else {
-		eprintln!(
-			"debug-storage: {pallet_name}.{item_name} is not a plain value storage (ty={:?})",
-			entry.ty
-		);
-		return;
-	};
-
-	let ty_dbg = format!("{:?}", entry.ty);
-	if let Some(type_id) = extract_plain_type_id(&ty_dbg) {
-		let type_path = api
-			.metadata()
-			.resolve_type(type_id)
-			.map(|t| t.path.to_string())
-			.filter(|s| !s.is_empty())
-			.unwrap_or_else(|| "<unknown>".to_string());
-		println!("debug-storage: type=Plain(id={type_id}, path={type_path})");
-	}
-
-	let key = val.key();
-	println!("debug-storage: key=0x{}", hex::encode(&key.0));
-	match api.get_opaque_storage_by_key(key, None) {
-		Ok(Some(raw)) => {
-			println!(
-				"debug-storage: value_bytes={} {}",
-				raw.len(),
-				hex_bytes_abbrev(&raw, 256)
+	let mut conn = Connection::open(&args.db)?;
+	ensure_db(&conn)?;
+	if keys.len() == 1 {
+		db_claim_legacy_rows(&conn, &keys[0].author_hex)?;
+	}
+
+	for key in &keys {
+		println!("{}: {}", i18n.pick("author", "作成者"), colors.author(&key.author_hex));
+	}
+	let authors: Vec<[u8; 32]> = keys.iter().map(|k| k.author_bytes).collect();
+	let summary = backfill_blocks(&api, &mut conn, &authors, epoch_size, from_block, to_block, |n, to| {
+		if n == to || n % 100 == 0 {
+			let done = n.saturating_sub(from_block) + 1;
+			let total = to.saturating_sub(from_block) + 1;
+			let percent = ((done * 100) / total.max(1)).min(100) as u8;
+			let line = format!(
+				"{} {} {}% (block {}/{})",
+				i18n.pick("backfill", "バックフィル"),
+				colors.dim(render_progress_bar(percent, 30)),
+				colors.epoch(percent.to_string()),
+				colors.range(n.to_string()),
+				colors.range(to.to_string())
 			);
-		}
-		Ok(None) => println!("debug-storage: value=null"),
-		Err(e) => eprintln!("debug-storage: read error: {e:?}"),
-	}
-}
-
-fn summarize_primitive(p: &Primitive) -> String {
-	match p {
-		Primitive::Bool(b) => b.to_string(),
-		Primitive::Char(c) => c.to_string(),
-		Primitive::String(s) => {
-			if s.len() <= 120 {
-				format!("{s:?}")
+			if is_tty {
+				print!("\r\x1b[2K{line}");
+				let _ = std::io::stdout().flush();
 			} else {
-				format!("{:?}...", &s[..120])
+				println!("{line}");
 			}
 		}
-		Primitive::U128(n) => n.to_string(),
-		Primitive::I128(n) => n.to_string(),
-		Primitive::U256(b) | Primitive::I256(b) => format!("0x{}", hex::encode(b)),
-	}
-}
-
-fn value_as_u64(v: &ScaleValue<()>) -> Option<u64> {
-	match &v.value {
-		ValueDef::Primitive(Primitive::U128(n)) => (*n).try_into().ok(),
-		_ => None,
+	})?;
+	if is_tty {
+		println!();
 	}
+	println!(
+		"{}: {} / {}: {} / {}: {}",
+		i18n.pick("blocks", "ブロック"),
+		summary.blocks_scanned,
+		i18n.pick("epochs", "エポック"),
+		summary.epochs,
+		i18n.pick("scheduled slots", "割り当てスロット"),
+		summary.scheduled_slots
+	);
+	Ok(())
 }
 
-fn value_as_unnamed(v: &ScaleValue<()>) -> Option<&[ScaleValue<()>]> {
-	match &v.value {
-		ValueDef::Composite(Composite::Unnamed(values)) => Some(values),
-		_ => None,
-	}
+#[derive(Args)]
+struct StatsArgs {
+	/// SQLite DB path
+	#[arg(long, default_value = "./mblog.db")]
+	db: String,
+	/// First epoch to include
+	#[arg(long, value_name = "EPOCH")]
+	from_epoch: Option<u64>,
+	/// Last epoch to include
+	#[arg(long, value_name = "EPOCH")]
+	to_epoch: Option<u64>,
+	/// Only slots planned at or after this time (same formats as `mblog log --since`)
+	#[arg(long, value_name = "TIME")]
+	since: Option<String>,
+	/// Only slots planned at or before this time
+	#[arg(long, value_name = "TIME")]
+	until: Option<String>,
+	/// Only slots of this Aura public key (hex)
+	#[arg(long, value_name = "PUBKEY")]
+	author: Option<String>,
+	/// Output format: table|json
+	#[arg(long, value_enum, default_value = "table")]
+	format: StatsFormat,
+	/// Output language for fixed messages: ja|en
+	#[arg(long, value_enum, default_value = "en")]
+	lang: Lang,
 }
 
-fn value_as_named(v: &ScaleValue<()>) -> Option<&[(String, ScaleValue<()>)]> {
-	match &v.value {
-		ValueDef::Composite(Composite::Named(values)) => Some(values),
-		_ => None,
-	}
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum StatsFormat {
+	Table,
+	Json,
 }
 
-fn value_as_bytes(v: &ScaleValue<()>) -> Option<Vec<u8>> {
-	let items = value_as_unnamed(v)?;
-	let mut out = Vec::with_capacity(items.len());
-	for it in items {
-		let Some(b) = value_as_u64(it) else { return None };
-		let b: u8 = b.try_into().ok()?;
-		out.push(b);
-	}
-	Some(out)
-}
+fn run_stats(args: StatsArgs) -> anyhow::Result<()> {
+	let conn = Connection::open(&args.db)?;
+	ensure_db(&conn)?;
+	let i18n = I18n::new(args.lang);
+	let now = chrono::Utc::now();
+	let filter = LogFilter {
+		from_epoch: args.from_epoch,
+		to_epoch: args.to_epoch,
+		since_utc: args.since.as_deref().map(|t| parse_log_time(t, now, "--since")).transpose()?,
+		until_utc: args.until.as_deref().map(|t| parse_log_time(t, now, "--until")).transpose()?,
+		author: args
+			.author
+			.as_deref()
+			.map(|a| parse_pubkey_hex(a).map(|b| hex0x(&b)))
+			.transpose()
+			.map_err(|e| anyhow!("invalid --author: {e}"))?,
+		..LogFilter::default()
+	};
+	let rows = db_fetch_log_rows(&conn, &filter)?;
+	let (epochs, total) = compute_stats(&rows);
 
-fn value_as_wrapped_bytes(v: &ScaleValue<()>) -> Option<Vec<u8>> {
-	// Many values appear as Composite::Unnamed(len=1) -> Composite::Unnamed(len=N) -> [u8...]
-	let items = value_as_unnamed(v)?;
-	if items.len() != 1 {
-		return None;
+	if let StatsFormat::Json = args.format {
+		let epochs: Vec<Value> = epochs
+			.iter()
+			.map(|(epoch, stats)| {
+				let mut v = stats.to_json();
+				v["epoch"] = (*epoch).into();
+				v
+			})
+			.collect();
+		let out = serde_json::json!({ "epochs": epochs, "total": total.to_json() });
+		println!("{}", serde_json::to_string_pretty(&out)?);
+		return Ok(());
 	}
-	value_as_bytes(&items[0])
-}
 
-fn value_as_wrapped_u64(v: &ScaleValue<()>) -> Option<u64> {
-	let items = value_as_unnamed(v)?;
-	if items.len() != 1 {
-		return None;
+	println!("Midnight Block Stats");
+	println!("--------------------");
+	println!();
+	if rows.is_empty() {
+		eprintln!("{}", i18n.pick("No block production logs match the filters.", "条件に一致するブロック生成ログがありません"));
+		return Ok(());
 	}
-	value_as_u64(&items[0])
+	let pct = |v: Option<f64>| v.map(|r| format!("{:.1}%", r * 100.0)).unwrap_or_else(|| "-".to_string());
+	let secs = |v: Option<f64>| v.map(|s| format!("{s:.1}")).unwrap_or_else(|| "-".to_string());
+	let cells = |label: String, s: &ProductionStats| {
+		vec![
+			label,
+			s.scheduled.to_string(),
+			s.minted.to_string(),
+			s.finalized.to_string(),
+			s.missed.to_string(),
+			s.orphaned.to_string(),
+			s.pending.to_string(),
+			pct(s.fill_rate()),
+			s.longest_miss_streak.to_string(),
+			secs(s.avg_delay_secs()),
+			secs(s.avg_lateness_secs()),
+			secs(s.avg_seen_latency_secs()),
+			secs(s.finality_lag_secs(0.5)),
+			secs(s.finality_lag_secs(0.95)),
+			secs(s.finality_lag_secs(1.0)),
+		]
+	};
+	let mut table: Vec<Vec<String>> = epochs.iter().map(|(epoch, s)| cells(epoch.to_string(), s)).collect();
+	table.push(cells("total".to_string(), &total));
+	print_table(
+		&[
+			"epoch",
+			"scheduled",
+			"minted",
+			"finalized",
+			"missed",
+			"orphaned",
+			"pending",
+			"fill_rate",
+			"longest_miss_streak",
+			"avg_delay_s",
+			"avg_lateness_s",
+			"avg_seen_latency_s",
+			"finality_lag_p50_s",
+			"finality_lag_p95_s",
+			"finality_lag_max_s",
+		],
+		&table,
+	);
+	println!();
+	println!(
+		"{}: {}",
+		i18n.pick("Miss streaks (2+ in a row)", "連続ミス（2回以上）"),
+		total.miss_streaks
+	);
+	Ok(())
 }
 
-fn hex0x(bytes: &[u8]) -> String {
-	format!("0x{}", hex::encode(bytes))
+#[derive(Args)]
+struct CommitteeArgs {
+	#[arg(long, default_value = "ws://127.0.0.1:9944")]
+	ws: String,
+	/// Path to a node keystore directory; its Aura keys are highlighted. Repeatable.
+	#[arg(long)]
+	keystore_path: Vec<String>,
+	/// Aura public key (hex) to highlight. Repeatable.
+	#[arg(long, value_name = "PUBKEY")]
+	author: Vec<String>,
+	/// Slots per epoch; only used when the runtime does not expose `Sidechain.SlotsPerEpoch` (default: 1200)
+	#[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+	epoch_size: Option<u32>,
+	/// SQLite DB path; block counts come from `chain_blocks` (`mblog block --watch --record-chain`)
+	#[arg(long, default_value = "./mblog.db")]
+	db: String,
+	/// Epoch to rank (default: current epoch)
+	#[arg(long)]
+	epoch: Option<u64>,
+	/// Output format: table|json
+	#[arg(long, value_enum, default_value = "table")]
+	format: StatsFormat,
+	/// Output language for fixed messages: ja|en
+	#[arg(long, value_enum, default_value = "en")]
+	lang: Lang,
+	/// Colorize output: auto|always|never
+	#[arg(long, value_enum, default_value = "auto")]
+	color: ColorMode,
 }
 
-fn debug_pretty_committee_info(v: &ScaleValue<()>, max_items: usize) -> Option<Vec<String>> {
-	// Expected shape:
-	// Composite::Named { epoch: (u64), committee: [slot -> (sidechain_pub_key(33), {aura(32), grandpa(32)})] }
-	let named = value_as_named(v)?;
-	let epoch_v = named.iter().find(|(k, _)| k == "epoch")?.1.clone();
-	let committee_v = named.iter().find(|(k, _)| k == "committee")?.1.clone();
-
-	let epoch = value_as_wrapped_u64(&epoch_v)?;
-	let committee_outer = value_as_unnamed(&committee_v)?;
-	if committee_outer.len() != 1 {
-		return None;
-	}
-	let schedule = value_as_unnamed(&committee_outer[0])?;
-
-	let mut out = Vec::new();
-	out.push(format!("CommitteeInfo(epoch={epoch}, slots={})", schedule.len()));
+fn run_committee(args: CommitteeArgs) -> anyhow::Result<()> {
+	let i18n = I18n::new(args.lang);
+	let colors = Colors::new(args.color);
+	let ours: Vec<String> = if args.keystore_path.is_empty() && args.author.is_empty() {
+		Vec::new()
+	} else {
+		collect_monitored_keys(&args.keystore_path, &args.author, false)?
+			.iter()
+			.map(|k| hex0x(&k.author_bytes))
+			.collect()
+	};
+	let conn = Connection::open(&args.db)?;
+	ensure_db(&conn)?;
+	let api = connect(&args.ws)?;
+	let epoch_size = resolve_epoch_size(&api, args.epoch_size, &i18n, &colors);
+	let slot_dur_ms = api.slot_duration_ms()?;
+	let ts_ms = api.timestamp_ms(None)?.unwrap_or(0);
+	let best_hash = api.best_hash()?.ok_or_else(|| anyhow!("no best head"))?;
+	let best_header = api.header(best_hash)?.ok_or_else(|| anyhow!("no best header"))?;
+	let latest_slot = aura_slot_from_header(&best_header).unwrap_or_else(|| ts_ms / slot_dur_ms.max(1));
+	let current_epoch = latest_slot / epoch_size;
+	let epoch = args.epoch.unwrap_or(current_epoch);
+
+	let (committee, source) = epoch_committee(&api, &conn, epoch, current_epoch)?;
+	if committee.is_empty() {
+		return Err(anyhow!("committee of epoch {epoch} is empty"));
+	}
+	let start_slot = epoch * epoch_size;
+	let end_slot = start_slot + epoch_size - 1;
+	let settled = settled_slot_ranges(&db_chain_finalized_blocks(&conn, epoch)?, start_slot, end_slot);
+	let produced: HashMap<String, u64> = db_chain_produced_counts(&conn, epoch)?.into_iter().collect();
+	let members = committee_stats(&committee, start_slot, epoch_size, &settled, &produced);
+	let total = MemberStats {
+		author: "total".to_string(),
+		seats: members.iter().map(|m| m.seats).sum(),
+		assigned: members.iter().map(|m| m.assigned).sum(),
+		settled: members.iter().map(|m| m.settled).sum(),
+		produced: members.iter().map(|m| m.produced.min(m.settled)).sum(),
+	};
 
-	let mut shown = 0usize;
-	for (idx, entry) in schedule.iter().enumerate() {
-		if shown >= max_items {
-			break;
-		}
-		let parts = value_as_unnamed(entry)?;
-		if parts.len() != 2 {
-			continue;
+	if let StatsFormat::Json = args.format {
+		let list: Vec<Value> = members
+			.iter()
+			.enumerate()
+			.map(|(i, m)| {
+				let mut v = m.to_json();
+				v["rank"] = (i + 1).into();
+				v["ours"] = ours.contains(&m.author).into();
+				v
+			})
+			.collect();
+		let mut total = total.to_json();
+		if let Some(obj) = total.as_object_mut() {
+			obj.remove("author");
 		}
-		let sidechain = value_as_wrapped_bytes(&parts[0])?;
-		let keys_named = value_as_named(&parts[1])?;
-		let aura_v = keys_named.iter().find(|(k, _)| k == "aura")?.1.clone();
-		let grandpa_v = keys_named.iter().find(|(k, _)| k == "grandpa")?.1.clone();
-		let aura = value_as_wrapped_bytes(&aura_v)?;
-		let grandpa = value_as_wrapped_bytes(&grandpa_v)?;
-
-		out.push(format!(
-			"  slot[{idx}]: sidechain={} aura={} grandpa={}",
-			hex0x(&sidechain),
-			hex0x(&aura),
-			hex0x(&grandpa)
-		));
-		shown += 1;
+		let out = serde_json::json!({
+			"epoch": epoch,
+			"start_slot": start_slot,
+			"end_slot": end_slot,
+			"source": source,
+			"committee_size": committee.len(),
+			"settled_ranges": settled
+				.iter()
+				.map(|(first, last)| serde_json::json!({ "first_slot": first, "last_slot": last }))
+				.collect::<Vec<_>>(),
+			"members": list,
+			"total": total,
+		});
+		println!("{}", serde_json::to_string_pretty(&out)?);
+		return Ok(());
 	}
 
-	if schedule.len() > shown {
-		out.push(format!("  ... ({} more)", schedule.len().saturating_sub(shown)));
+	println!("Midnight Committee");
+	println!("------------------");
+	println!();
+	println!(
+		"{}:{} {}",
+		i18n.pick("epoch", "エポック"),
+		colors.epoch(epoch.to_string()),
+		colors.dim(format!("({start_slot}..{end_slot}, {source}, {} {})", committee.len(), i18n.pick("seats", "席")))
+	);
+	if settled.is_empty() {
+		eprintln!(
+			"{}",
+			colors.error(i18n.pick(
+				"No finalized block of this epoch in chain_blocks (run `mblog block --watch --record-chain`).",
+				"chain_blocks にこのエポックのファイナライズ済みブロックがありません（`mblog block --watch --record-chain` を実行してください）",
+			))
+		);
+	} else {
+		let ranges: Vec<String> = settled
+			.iter()
+			.map(|(first, last)| format!("{}..{}", colors.range(first.to_string()), colors.range(last.to_string())))
+			.collect();
+		println!("{}: {}", i18n.pick("recorded slots", "記録済みスロット"), ranges.join(", "));
 	}
-	Some(out)
-}
+	println!();
 
-fn fetch_committee_info(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	pallet_name: &str,
-	item_name: &str,
-) -> anyhow::Result<Option<(u64, Vec<([u8; 32], [u8; 32])>)>> {
-	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
-		return Ok(None);
-	};
-	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
-		return Ok(None);
+	let pct = |v: Option<f64>| v.map(|r| format!("{:.1}%", r * 100.0)).unwrap_or_else(|| "-".to_string());
+	let cells = |rank: String, mark: &str, m: &MemberStats| {
+		vec![
+			rank,
+			mark.to_string(),
+			m.author.clone(),
+			m.seats.to_string(),
+			m.assigned.to_string(),
+			m.produced.to_string(),
+			m.missed().to_string(),
+			m.pending().to_string(),
+			pct(m.fill_rate()),
+		]
 	};
-	let ty_dbg = format!("{:?}", entry.ty);
-	let Some(type_id) = extract_plain_type_id(&ty_dbg) else {
-		return Ok(None);
-	};
-	let Ok(val) = entry.get_value(pallet_name) else {
-		return Ok(None);
-	};
-	let key = val.key();
-	let raw = match api.get_opaque_storage_by_key(key, None) {
-		Ok(Some(b)) => b,
-		Ok(None) => return Ok(None),
-		Err(e) => return Err(anyhow!("{e:?}")),
-	};
-	let v = ScaleValue::<()>::decode_as_type(&mut raw.as_slice(), type_id, api.metadata().types())
-		.map_err(|e| anyhow!("{e}"))?;
-
-	let named = value_as_named(&v).ok_or_else(|| anyhow!("CommitteeInfo decode: expected named composite"))?;
-	let epoch_v = named
+	let mut table: Vec<Vec<String>> = members
 		.iter()
-		.find(|(k, _)| k == "epoch")
-		.ok_or_else(|| anyhow!("CommitteeInfo decode: missing epoch"))?
-		.1
-		.clone();
-	let committee_v = named
-		.iter()
-		.find(|(k, _)| k == "committee")
-		.ok_or_else(|| anyhow!("CommitteeInfo decode: missing committee"))?
-		.1
-		.clone();
-
-	let epoch = value_as_wrapped_u64(&epoch_v).ok_or_else(|| anyhow!("CommitteeInfo decode: epoch"))?;
-
-	let committee_outer = value_as_unnamed(&committee_v).ok_or_else(|| anyhow!("CommitteeInfo decode: committee outer"))?;
-	if committee_outer.len() != 1 {
-		return Err(anyhow!(
-			"CommitteeInfo decode: expected committee outer len=1, got {}",
-			committee_outer.len()
-		));
-	}
-	let schedule = value_as_unnamed(&committee_outer[0]).ok_or_else(|| anyhow!("CommitteeInfo decode: schedule"))?;
+		.enumerate()
+		.map(|(i, m)| cells((i + 1).to_string(), if ours.contains(&m.author) { "*" } else { "" }, m))
+		.collect();
+	table.push(cells("-".to_string(), "", &total));
+	print_table(
+		&["#", "ours", "author", "seats", "assigned", "produced", "missed", "pending", "fill_rate"],
+		&table,
+	);
+	println!();
 
-	let mut out: Vec<([u8; 32], [u8; 32])> = Vec::with_capacity(schedule.len());
-	for entry in schedule {
-		let parts = value_as_unnamed(entry).ok_or_else(|| anyhow!("CommitteeInfo decode: entry parts"))?;
-		if parts.len() != 2 {
-			return Err(anyhow!("CommitteeInfo decode: expected entry len=2, got {}", parts.len()));
+	for author in &ours {
+		match members.iter().position(|m| m.author == *author) {
+			Some(i) => {
+				let m = &members[i];
+				let line = format!(
+					"{}: {}/{}, {}: {} ({}: {})",
+					i18n.pick("rank", "順位"),
+					i + 1,
+					members.len(),
+					i18n.pick("fill rate", "生成率"),
+					pct(m.fill_rate()),
+					i18n.pick("committee", "委員会全体"),
+					pct(total.fill_rate())
+				);
+				let below = m.fill_rate().zip(total.fill_rate()).is_some_and(|(ours, all)| ours < all);
+				println!(
+					"{} {}",
+					colors.author(author),
+					if below { colors.error(line) } else { colors.ok(line) }
+				);
+			}
+			None => println!(
+				"{} {}",
+				colors.author(author),
+				colors.dim(i18n.pick("not in the committee of this epoch", "このエポックの委員会にいません"))
+			),
 		}
-		let keys_named =
-			value_as_named(&parts[1]).ok_or_else(|| anyhow!("CommitteeInfo decode: keys named"))?;
-		let aura_v = keys_named
-			.iter()
-			.find(|(k, _)| k == "aura")
-			.ok_or_else(|| anyhow!("CommitteeInfo decode: missing aura"))?
-			.1
-			.clone();
-		let grandpa_v = keys_named
-			.iter()
-			.find(|(k, _)| k == "grandpa")
-			.ok_or_else(|| anyhow!("CommitteeInfo decode: missing grandpa"))?
-			.1
-			.clone();
-		let aura_bytes =
-			value_as_wrapped_bytes(&aura_v).ok_or_else(|| anyhow!("CommitteeInfo decode: aura bytes"))?;
-		let grandpa_bytes = value_as_wrapped_bytes(&grandpa_v)
-			.ok_or_else(|| anyhow!("CommitteeInfo decode: grandpa bytes"))?;
-		let aura_len = aura_bytes.len();
-		let aura_arr: [u8; 32] = aura_bytes
-			.try_into()
-			.map_err(|_| anyhow!("CommitteeInfo decode: aura len={aura_len}"))?;
-		let grandpa_len = grandpa_bytes.len();
-		let grandpa_arr: [u8; 32] = grandpa_bytes
-			.try_into()
-			.map_err(|_| anyhow!("CommitteeInfo decode: grandpa len={grandpa_len}"))?;
-		out.push((aura_arr, grandpa_arr));
 	}
-
-	Ok(Some((epoch, out)))
+	Ok(())
 }
 
-fn print_next_committee_for_author(
+#[allow(clippy::too_many_arguments)]
+fn print_next_committee_for_author<C: ChainSource>(
 	i18n: &I18n,
 	colors: &Colors,
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
+	src: &C,
 	author_bytes: &[u8; 32],
 	latest_slot: u64,
 	ts_ms: u64,
 	slot_dur_ms: u64,
 	next_start_slot: u64,
+	epoch_size: u64,
 	out_tz: &OutputTz,
 	utc_tz: &OutputTz,
 ) {
-	let res = fetch_committee_info(api, "SessionCommitteeManagement", "NextCommittee");
-	let (next_epoch, schedule) = match res {
+	let (next_epoch, schedule) = match src.next_committee() {
 		Ok(Some(v)) => v,
 		Ok(None) => {
 			println!(
@@ -1123,12 +978,7 @@ fn print_next_committee_for_author(
 		}
 	};
 
-	let mut my: Vec<u64> = Vec::new();
-	for (i, (aura, _grandpa)) in schedule.iter().enumerate() {
-		if aura == author_bytes {
-			my.push(next_start_slot + (i as u64));
-		}
-	}
+	let my = committee_slots(&schedule, author_bytes, next_start_slot, epoch_size);
 
 	println!(
 		"{}: {}",
@@ -1143,8 +993,7 @@ fn print_next_committee_for_author(
 	}
 
 	for (idx, slot) in my.iter().enumerate() {
-		let delta_slots = *slot as i64 - latest_slot as i64;
-		let ts = ts_ms as i64 + (delta_slots * slot_dur_ms as i64);
+		let ts = planned_ts_ms(*slot, latest_slot, ts_ms, slot_dur_ms);
 		let out_ts = colors.time(format_ts(ts, out_tz));
 		let utc_ts = format_ts(ts, utc_tz);
 		println!(
@@ -1158,460 +1007,132 @@ fn print_next_committee_for_author(
 	println!("{}={}", i18n.pick("Total", "合計"), my.len());
 }
 
-fn summarize_value_lines(
-	v: &ScaleValue<()>,
-	depth: usize,
-	indent: usize,
-	max_depth: usize,
-	max_items: usize,
-) -> Vec<String> {
-	let pad = " ".repeat(indent);
-	match &v.value {
-		ValueDef::Primitive(p) => vec![format!("{pad}{}", summarize_primitive(p))],
-		ValueDef::BitSequence(bits) => vec![format!("{pad}bits(len={})", bits.len())],
-		ValueDef::Variant(Variant { name, values }) => {
-			let mut out = vec![format!("{pad}Variant({name})")];
-			if depth >= max_depth {
-				out.push(format!("{pad}  ..."));
-				return out;
-			}
-			out.extend(summarize_composite_lines(
-				values,
-				depth + 1,
-				indent + 2,
-				max_depth,
-				max_items,
-			));
-			out
-		}
-		ValueDef::Composite(c) => summarize_composite_lines(c, depth, indent, max_depth, max_items),
-	}
-}
-
-fn summarize_composite_lines(
-	c: &Composite<()>,
-	depth: usize,
-	indent: usize,
-	max_depth: usize,
-	max_items: usize,
-) -> Vec<String> {
-	let pad = " ".repeat(indent);
-	match c {
-		Composite::Named(fields) => {
-			let mut out = vec![format!("{pad}Composite::Named(len={})", fields.len())];
-			if depth >= max_depth {
-				out.push(format!("{pad}  ..."));
-				return out;
-			}
-			for (idx, (k, v)) in fields.iter().enumerate() {
-				if idx >= max_items {
-					out.push(format!("{pad}  ... ({} more)", fields.len().saturating_sub(max_items)));
-					break;
-				}
-				let mut lines = summarize_value_lines(v, depth + 1, indent + 4, max_depth, max_items);
-				if let Some(first) = lines.first_mut() {
-					*first = format!("{pad}  {k}: {}", first.trim_start());
-				}
-				out.extend(lines);
-			}
-			out
-		}
-		Composite::Unnamed(values) => {
-			let mut out = vec![format!("{pad}Composite::Unnamed(len={})", values.len())];
-			if depth >= max_depth {
-				out.push(format!("{pad}  ..."));
-				return out;
-			}
-			for (idx, v) in values.iter().enumerate() {
-				if idx >= max_items {
-					out.push(format!("{pad}  ... ({} more)", values.len().saturating_sub(max_items)));
-					break;
-				}
-				let mut lines = summarize_value_lines(v, depth + 1, indent + 4, max_depth, max_items);
-				if let Some(first) = lines.first_mut() {
-					*first = format!("{pad}  [{idx}]: {}", first.trim_start());
-				}
-				out.extend(lines);
-			}
-			out
-		}
-	}
-}
-
-fn debug_decode_plain_storage(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	pallet_name: &str,
-	item_name: &str,
-	max_depth: usize,
-	max_items: usize,
-) {
-	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
-		eprintln!("debug-decode-storage: pallet not found: {pallet_name}");
-		return;
-	};
-	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
-		eprintln!("debug-decode-storage: storage item not found: {pallet_name}.{item_name}");
-		return;
-	};
-	let ty_dbg = format!("{:?}", entry.ty);
-	let Some(type_id) = extract_plain_type_id(&ty_dbg) else {
-		eprintln!("debug-decode-storage: storage is not Plain: {ty_dbg}");
-		return;
+fn run_block(args: BlockArgs) -> anyhow::Result<()> {
+	let conn = Connection::open(&args.db)?;
+	ensure_db(&conn)?;
+	let out_tz = parse_output_tz(&args.tz)?;
+	let i18n = I18n::new(args.lang);
+	let author_filter = args
+		.author
+		.as_deref()
+		.map(|a| parse_pubkey_hex(a).map(|b| hex0x(&b)))
+		.transpose()
+		.map_err(|e| anyhow!("invalid --author: {e}"))?;
+	let now = chrono::Utc::now();
+	let mut filter = LogFilter {
+		from_epoch: args.from_epoch,
+		to_epoch: args.to_epoch,
+		since_utc: args.since.as_deref().map(|t| parse_log_time(t, now, "--since")).transpose()?,
+		until_utc: args.until.as_deref().map(|t| parse_log_time(t, now, "--until")).transpose()?,
+		statuses: args.status.clone(),
+		author: author_filter,
+		slots: args.slot.clone(),
+		last: args.last,
 	};
-	let type_path = api
-		.metadata()
-		.resolve_type(type_id)
-		.map(|t| t.path.to_string())
-		.filter(|s| !s.is_empty())
-		.unwrap_or_else(|| "<unknown>".to_string());
-
-	let Ok(val) = entry.get_value(pallet_name) else {
-		eprintln!("debug-decode-storage: {pallet_name}.{item_name} is not a plain value storage");
-		return;
+	// Without any range filter, show a single epoch (--epoch or the latest) as before.
+	let single_epoch = args.epoch.is_some()
+		|| (filter.from_epoch.is_none()
+			&& filter.to_epoch.is_none()
+			&& filter.since_utc.is_none()
+			&& filter.until_utc.is_none()
+			&& filter.slots.is_empty()
+			&& filter.last.is_none());
+	let epoch = if single_epoch {
+		let epoch = match args.epoch {
+			Some(e) => e,
+			None => conn
+				.query_row("SELECT MAX(epoch) FROM epoch_info", [], |r| r.get::<_, Option<i64>>(0))
+				.or_else(|_| conn.query_row("SELECT MAX(epoch) FROM blocks", [], |r| r.get::<_, Option<i64>>(0)))?
+				.map(|v| v as u64)
+				.ok_or_else(|| anyhow!("no epoch found in DB (epoch_info/blocks empty)"))?,
+		};
+		filter.from_epoch = Some(epoch);
+		filter.to_epoch = Some(epoch);
+		Some(epoch)
+	} else {
+		None
 	};
-	let key = val.key();
-	let raw = match api.get_opaque_storage_by_key(key, None) {
-		Ok(Some(b)) => b,
-		Ok(None) => {
-			println!("debug-decode-storage: value=null");
-			return;
-		}
-		Err(e) => {
-			eprintln!("debug-decode-storage: read error: {e:?}");
-			return;
+	let rows = db_fetch_log_rows(&conn, &filter)?;
+
+	match args.format {
+		LogFormat::Table => {}
+		LogFormat::Json => {
+			let list: Vec<Value> = rows.iter().map(LogRow::to_json).collect();
+			println!("{}", serde_json::to_string_pretty(&list)?);
+			return Ok(());
 		}
-	};
-
-	println!("debug-decode-storage: type=Plain(id={type_id}, path={type_path})");
-	match ScaleValue::<()>::decode_as_type(&mut raw.as_slice(), type_id, api.metadata().types()) {
-		Ok(v) => {
-			println!("debug-decode-storage: decoded (summary)");
-			if type_path == "pallet_session_validator_management::pallet::CommitteeInfo" {
-				if let Some(lines) = debug_pretty_committee_info(&v, max_items) {
-					for line in lines {
-						println!("{line}");
-					}
-					return;
-				}
-			}
-			for line in summarize_value_lines(&v, 0, 0, max_depth, max_items) {
-				println!("{line}");
+		LogFormat::Ndjson => {
+			for row in &rows {
+				println!("{}", row.to_json());
 			}
+			return Ok(());
 		}
-		Err(e) => {
-			eprintln!("debug-decode-storage: decode error: {e}");
-		}
-	}
-}
-
-fn debug_list_storage(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	pallet_name: &str,
-	filter: Option<&str>,
-) {
-	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
-		eprintln!("debug-storage-list: pallet not found: {pallet_name}");
-		return;
-	};
-
-	let filter_lc = filter.map(|s| s.to_lowercase());
-	println!("{pallet_name} storage entries");
-	println!("---------------------");
-	for entry in pallet.storage() {
-		let name = entry.name.as_str();
-		if let Some(ref f) = filter_lc {
-			if !name.to_lowercase().contains(f) {
-				continue;
+		LogFormat::Csv => {
+			println!("{}", LOG_FIELDS.join(","));
+			for row in &rows {
+				println!("{}", row.to_csv_record());
 			}
-		}
-		let ty_dbg = format!("{:?}", entry.ty);
-		if let Some(type_id) = extract_plain_type_id(&ty_dbg) {
-			let type_path = api
-				.metadata()
-				.resolve_type(type_id)
-				.map(|t| t.path.to_string())
-				.filter(|s| !s.is_empty())
-				.unwrap_or_else(|| "<unknown>".to_string());
-			println!("{name}: Plain(id={type_id}, path={type_path})");
-		} else {
-			println!("{name}: {ty_dbg}");
+			return Ok(());
 		}
 	}
-}
-
-fn debug_session_metadata(
-	api: &Api<DefaultRuntimeConfig, TungsteniteRpcClient>,
-	author_bytes: &[u8; 32],
-) -> Vec<(String, String)> {
-	let mut rows = Vec::new();
-
-	rows.push((
-		"meta Session pallet".to_string(),
-		if api.metadata().pallet_by_name("Session").is_some() {
-			"present".to_string()
-		} else {
-			"missing".to_string()
-		},
-	));
-
-	let check = |pallet: &'static str, item: &'static str| -> Result<(), anyhow::Error> {
-		api.metadata()
-			.storage_value_key(pallet, item)
-			.map(|_| ())
-			.map_err(|e| anyhow!("{e:?}"))
-	};
-	let check_map = |pallet: &'static str, item: &'static str| -> Result<(), anyhow::Error> {
-		api.metadata()
-			.storage_map_key_prefix(pallet, item)
-			.map(|_| ())
-			.map_err(|e| anyhow!("{e:?}"))
-	};
-	let check_double_prefix =
-		|pallet: &'static str, item: &'static str, first: KeyTypeId| -> Result<(), anyhow::Error> {
-			api.metadata()
-				.storage_double_map_key_prefix(pallet, item, first)
-				.map(|_| ())
-				.map_err(|e| anyhow!("{e:?}"))
-		};
-
-	rows.push((
-		"meta Session.Validators".to_string(),
-		format_meta_check(check("Session", "Validators")),
-	));
-	rows.push((
-		"meta Session.QueuedValidators".to_string(),
-		format_meta_check(check("Session", "QueuedValidators")),
-	));
-	rows.push((
-		"meta Session.QueuedKeys".to_string(),
-		format_meta_check(check("Session", "QueuedKeys")),
-	));
-	rows.push((
-		"meta Session.NextKeys(map)".to_string(),
-		format_meta_check(check_map("Session", "NextKeys")),
-	));
-	rows.push((
-		"meta Session.KeyOwner(dbl)".to_string(),
-		format_meta_check(check_double_prefix("Session", "KeyOwner", KeyTypeId(*b"aura"))),
-	));
-
-	// State checks (do not swallow errors)
-	match api.get_storage::<Vec<AccountId32>>("Session", "Validators", None) {
-		Ok(Some(vs)) => rows.push(("state Session.Validators".to_string(), format!("len={}", vs.len()))),
-		Ok(None) => rows.push(("state Session.Validators".to_string(), "null".to_string())),
-		Err(e) => rows.push(("state Session.Validators".to_string(), format!("err: {e:?}"))),
-	}
-	match api.get_storage::<Vec<AccountId32>>("Session", "QueuedValidators", None) {
-		Ok(Some(vs)) => rows.push((
-			"state Session.QueuedValidators".to_string(),
-			format!("len={}", vs.len()),
-		)),
-		Ok(None) => rows.push(("state Session.QueuedValidators".to_string(), "null".to_string())),
-		Err(e) => rows.push(("state Session.QueuedValidators".to_string(), format!("err: {e:?}"))),
-	}
-
-	let key_type = KeyTypeId(*b"aura");
-	let pubkey = author_bytes.to_vec();
-	match api.get_storage_double_map::<KeyTypeId, Vec<u8>, AccountId32>("Session", "KeyOwner", key_type, pubkey, None)
-	{
-		Ok(Some(a)) => rows.push(("state Session.KeyOwner(aura)".to_string(), hex_account_id32(&a))),
-		Ok(None) => rows.push(("state Session.KeyOwner(aura)".to_string(), "null".to_string())),
-		Err(e) => rows.push(("state Session.KeyOwner(aura)".to_string(), format!("err: {e:?}"))),
-	}
-
-	rows
-}
-
-fn account_id32_bytes(a: &AccountId32) -> &[u8] {
-	<AccountId32 as AsRef<[u8]>>::as_ref(a)
-}
-
-fn hex_account_id32(a: &AccountId32) -> String {
-	format!("0x{}", hex::encode(account_id32_bytes(a)))
-}
-
-fn hash_authorities(auths: &[sr25519::Public]) -> [u8; 32] {
-	let mut hasher = Sha256::new();
-	for a in auths {
-		let bytes: &[u8] = a.as_ref();
-		hasher.update(bytes);
-	}
-	hasher.finalize().into()
-}
-
-fn author_in_authorities(author_bytes: &[u8; 32], auths: &[sr25519::Public]) -> bool {
-	auths.iter().any(|a| {
-		let bytes: &[u8] = a.as_ref();
-		bytes == author_bytes.as_slice()
-	})
-}
-
-fn schedule_hash(slots: &[u64]) -> [u8; 32] {
-	let mut hasher = Sha256::new();
-	for s in slots {
-		hasher.update(s.to_le_bytes());
-	}
-	hasher.finalize().into()
-}
-
-fn compute_my_slots(
-	auths: &[sr25519::Public],
-	author_bytes: &[u8; 32],
-	start_slot: u64,
-	slots_to_scan: u64,
-) -> Vec<u64> {
-	let mut out = Vec::new();
-	if auths.is_empty() {
-		return out;
-	}
-	for i in 0..slots_to_scan {
-		let slot = start_slot + i;
-		let who = &auths[(slot as usize) % auths.len()];
-		let who_bytes: &[u8] = who.as_ref();
-		if who_bytes == author_bytes.as_slice() {
-			out.push(slot);
-		}
-	}
-	out
-}
-
-fn print_kv_table(rows: &[(String, String)]) {
-	let max_w = rows
-		.iter()
-		.map(|(k, _)| UnicodeWidthStr::width(k.as_str()))
-		.max()
-		.unwrap_or(0);
-	for (k, v) in rows {
-		let w = UnicodeWidthStr::width(k.as_str());
-		let pad = max_w.saturating_sub(w);
-		println!("{}{}: {}", " ".repeat(pad), k, v);
-	}
-}
-
-fn format_rfc3339_in_tz(s: &str, out_tz: &OutputTz) -> String {
-	let s = s.trim();
-	if s.is_empty() || s == "-" {
-		return "-".to_string();
-	}
-	let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) else {
-		return s.to_string();
-	};
-	let dt_utc = dt.with_timezone(&Utc);
-	match out_tz {
-		OutputTz::Utc => dt_utc.to_rfc3339(),
-		OutputTz::Local | OutputTz::ForcedLocal => dt_utc.with_timezone(&Local).to_rfc3339(),
-		OutputTz::Fixed(off) => dt_utc.with_timezone(off).to_rfc3339(),
-	}
-}
-
-fn print_table(headers: &[&str], rows: &[Vec<String>]) {
-	let mut widths: Vec<usize> = headers.iter().map(|h| UnicodeWidthStr::width(*h)).collect();
-	for row in rows {
-		for (i, cell) in row.iter().enumerate() {
-			let w = UnicodeWidthStr::width(cell.as_str());
-			if w > widths[i] {
-				widths[i] = w;
-			}
-		}
-	}
-
-	let border = {
-		let mut s = String::new();
-		s.push('|');
-		for w in &widths {
-			s.push_str(&"=".repeat(*w + 2));
-			s.push('|');
-		}
-		s
-	};
-
-	println!("{border}");
-	println!(
-		"|{}|",
-		headers
-			.iter()
-			.enumerate()
-			.map(|(i, h)| format!(" {:<width$} ", *h, width = widths[i]))
-			.collect::<Vec<_>>()
-			.join("|")
-	);
-	println!("{border}");
-	for row in rows {
-		println!(
-			"|{}|",
-			row.iter()
-				.enumerate()
-				.map(|(i, c)| format!(" {:<width$} ", c, width = widths[i]))
-				.collect::<Vec<_>>()
-				.join("|")
-		);
-	}
-	println!("{border}");
-}
-
-fn run_block(args: BlockArgs) -> anyhow::Result<()> {
-	let conn = Connection::open(&args.db)?;
-	let out_tz = parse_output_tz(&args.tz)?;
-	let i18n = I18n::new(args.lang);
-	let epoch = match args.epoch {
-		Some(e) => e,
-		None => conn
-			.query_row("SELECT MAX(epoch) FROM epoch_info", [], |r| r.get::<_, Option<i64>>(0))
-			.or_else(|_| conn.query_row("SELECT MAX(epoch) FROM blocks", [], |r| r.get::<_, Option<i64>>(0)))?
-			.map(|v| v as u64)
-			.ok_or_else(|| anyhow!("no epoch found in DB (epoch_info/blocks empty)"))?,
-	};
-	let mut stmt = conn.prepare(
-		"SELECT b.slot, b.status, b.block_number, b.block_hash, b.planned_time_utc, e.start_slot \
-		 FROM blocks b LEFT JOIN epoch_info e ON b.epoch = e.epoch \
-		 WHERE b.epoch = ?1 ORDER BY b.slot ASC",
-	)?;
 
 	let mut rows_out: Vec<Vec<String>> = Vec::new();
-	let mut idx: u64 = 0;
-	let mut start_slot: Option<u64> = None;
-
-	let mut rows = stmt.query([epoch as i64])?;
-	while let Some(row) = rows.next()? {
-		idx += 1;
-		let slot: u64 = row.get::<_, i64>(0)? as u64;
-		let status: String = row.get(1)?;
-		let block_number: Option<i64> = row.get(2)?;
-		let block_hash: Option<String> = row.get(3)?;
-		let planned: Option<String> = row.get(4)?;
-		let st: Option<i64> = row.get(5)?;
-		if start_slot.is_none() {
-			start_slot = st.map(|v| v as u64);
-		}
-
-		let slot_in_epoch = start_slot.map(|s| slot.saturating_sub(s));
-		let bn = block_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
-		let hash = block_hash.unwrap_or_else(|| "-".to_string());
-		let scheduled = planned
-			.as_deref()
-			.map(|s| format_rfc3339_in_tz(s, &out_tz))
-			.unwrap_or_else(|| "-".to_string());
-
+	let mut authors: Vec<String> = Vec::new();
+	let mut epochs: Vec<String> = Vec::new();
+	for (i, row) in rows.iter().enumerate() {
+		authors.push(row.author.clone().unwrap_or_else(|| "-".to_string()));
+		epochs.push(row.epoch.to_string());
 		rows_out.push(vec![
-			idx.to_string(),
-			status,
-			bn,
-			slot.to_string(),
-			slot_in_epoch.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
-			scheduled,
-			hash,
+			(i + 1).to_string(),
+			row.status.clone(),
+			row.block_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+			row.slot.to_string(),
+			row.slot_in_epoch().map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
+			format_rfc3339_in_tz(&row.planned_time_utc, &out_tz),
+			row.block_hash.clone().unwrap_or_else(|| "-".to_string()),
 		]);
 	}
 
 	println!("Midnight Block Log");
 	println!("-------------------");
 	println!();
-	println!("epoch: {}", epoch);
+	match (epoch, rows.first(), rows.last()) {
+		(Some(epoch), _, _) => println!("epoch: {epoch}"),
+		(None, Some(first), Some(last)) => println!("epochs: {}..{}", first.epoch, last.epoch),
+		_ => {}
+	}
+	// Position and seat count of each key in the epoch's authority set, once it was recorded.
+	if epoch.is_some() {
+		let mut shown: Vec<&str> = Vec::new();
+		let multi_author = rows.iter().any(|r| r.author != rows[0].author);
+		for row in &rows {
+			let (Some(author), Some(seats)) = (row.author.as_deref(), row.authority_seats) else {
+				continue;
+			};
+			if shown.contains(&author) {
+				continue;
+			}
+			shown.push(author);
+			let prefix = if multi_author { format!("{author} ") } else { String::new() };
+			println!(
+				"{prefix}{}: {} ({}: {}/{})",
+				i18n.pick("position", "位置"),
+				row.authority_position.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+				i18n.pick("seats", "席"),
+				seats,
+				row.authority_set_len.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string())
+			);
+		}
+	}
 	if rows_out.is_empty() {
-		let msg = i18n.pick(
-			"No block production logs for this epoch.",
-			"このエポックにはブロック生成ログがありません",
-		);
+		let msg = if epoch.is_some() {
+			i18n.pick(
+				"No block production logs for this epoch.",
+				"このエポックにはブロック生成ログがありません",
+			)
+		} else {
+			i18n.pick("No block production logs match the filters.", "条件に一致するブロック生成ログがありません")
+		};
 		eprintln!();
 		if std::io::stdout().is_terminal() {
 			eprintln!("\x1b[31m{msg}\x1b[0m");
@@ -1621,107 +1142,32 @@ fn run_block(args: BlockArgs) -> anyhow::Result<()> {
 		eprintln!();
 		return Ok(());
 	}
-	print_table(
-		&[
-			"#",
-			"status",
-			"block_number",
-			"slot",
-			"slot_in_epoch",
-			"Scheduled_time",
-			"block_hash",
-		],
-		&rows_out,
-	);
-	println!();
-	Ok(())
-}
-
-fn jsonrpc_http_call(
-	client: &reqwest::blocking::Client,
-	endpoint: &str,
-	method: &str,
-	params: Value,
-) -> anyhow::Result<Value> {
-	let request_body = serde_json::json!({
-		"jsonrpc": "2.0",
-		"id": 1,
-		"method": method,
-		"params": params,
-	});
-	let res: Value = client
-		.post(endpoint)
-		.json(&request_body)
-		.send()?
-		.error_for_status()?
-		.json()?;
-	if let Some(err) = res.get("error") {
-		return Err(anyhow!("JSON-RPC error: {}", serde_json::to_string(err)?));
-	}
-	Ok(res
-		.get("result")
-		.cloned()
-		.unwrap_or(Value::Null))
-}
-
-fn parse_lovelace(v: &Value) -> Option<u128> {
-	match v {
-		Value::Number(n) => n.as_u64().map(|u| u as u128),
-		Value::String(s) => s.parse::<u128>().ok(),
-		_ => None,
-	}
-}
-
-fn format_ada_from_lovelace(lovelace: u128) -> String {
-	const UNIT: u128 = 1_000_000;
-	let whole = lovelace / UNIT;
-	let frac = (lovelace % UNIT) as u64;
-	if frac == 0 {
-		return whole.to_string();
-	}
-	let mut frac_s = format!("{frac:06}");
-	while frac_s.ends_with('0') {
-		frac_s.pop();
+	let mut headers = vec![
+		"#",
+		"status",
+		"block_number",
+		"slot",
+		"slot_in_epoch",
+		"Scheduled_time",
+		"block_hash",
+	];
+	// Several monitored keys in this epoch: show whose slot each row is.
+	if authors.iter().any(|a| *a != authors[0]) {
+		headers.insert(1, "author");
+		for (row, author) in rows_out.iter_mut().zip(authors) {
+			row.insert(1, author);
+		}
 	}
-	format!("{whole}.{frac_s}")
-}
-
-fn fetch_registration_status(
-	client: &reqwest::blocking::Client,
-	endpoint: &str,
-	sidechain_pubkey: &str,
-	mainchain_epoch: u64,
-) -> anyhow::Result<(u128, bool)> {
-	let ariadne = jsonrpc_http_call(
-		client,
-		endpoint,
-		"sidechain_getAriadneParameters",
-		Value::Array(vec![Value::Number(mainchain_epoch.into())]),
-	)?;
-
-	let Some(regs) = ariadne
-		.get("candidateRegistrations")
-		.and_then(|v| v.as_object())
-	else {
-		return Ok((0, false));
-	};
-
-	for (_mainchain_pubkey, entries) in regs {
-		let Some(arr) = entries.as_array() else { continue };
-		for entry in arr {
-			let sc = entry.get("sidechainPubKey").and_then(|v| v.as_str());
-			if sc != Some(sidechain_pubkey) {
-				continue;
-			}
-			let stake = entry
-				.get("stakeDelegation")
-				.and_then(parse_lovelace)
-				.unwrap_or(0);
-			let is_valid = entry.get("isValid").and_then(|v| v.as_bool()).unwrap_or(false);
-			return Ok((stake, is_valid));
+	// Rows from several epochs: show the epoch of each row.
+	if epochs.iter().any(|e| *e != epochs[0]) {
+		headers.insert(1, "epoch");
+		for (row, epoch) in rows_out.iter_mut().zip(epochs) {
+			row.insert(1, epoch);
 		}
 	}
-	Ok((0, false))
+	print_table(&headers, &rows_out);
+	println!();
+	Ok(())
 }
 
 fn run(common: CommonArgs) -> anyhow::Result<()> {
@@ -1729,15 +1175,12 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 	let colors = Colors::new(common.color);
 	let is_tty = std::io::stdout().is_terminal();
 	let live_update = common.watch && is_tty;
-	let author_hex =
-		detect_aura_pubkey_from_keystore(Path::new(&common.keystore_path))?;
-	let author_bytes =
-		parse_pubkey_hex(&author_hex).map_err(|e| anyhow!("invalid aura pubkey from keystore: {e}"))?;
-	let sidechain_pubkey = if common.no_registration_check || common.output_json {
-		None
-	} else {
-		Some(detect_sidechain_pubkey_from_keystore(Path::new(&common.keystore_path))?)
-	};
+	let keys = collect_monitored_keys(
+		&common.keystore_path,
+		&common.author,
+		!(common.no_registration_check || common.output_json),
+	)?;
+	let multi_key = keys.len() > 1;
 	let ariadne_client = if common.no_registration_check || common.output_json {
 		None
 	} else {
@@ -1751,85 +1194,153 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 	let out_tz = parse_output_tz(&common.tz)?;
 	let utc_tz = OutputTz::Utc;
 
-    let client = TungsteniteRpcClient::new(&common.ws, 3)
-		.map_err(|e| anyhow!("rpc client init failed: {e:?}"))?;
-    let api: Api<DefaultRuntimeConfig, TungsteniteRpcClient> =
-		Api::new(client).map_err(|e| anyhow!("api init failed: {e:?}"))?;
-
-	let has = author_has_aura_key(&api, &author_hex)?;
-	if !has {
-		return Err(anyhow!(
-			"Refusing to run: detected Aura key {} is not present in this node's keystore (author_hasKey=false).",
-			author_hex
-		));
+	let mut api = connect(&common.ws)?;
+
+	for key in keys.iter().filter(|k| k.from_keystore) {
+		let has = author_has_aura_key(&api, &key.author_hex)?;
+		if has {
+			continue;
+		}
+		if !multi_key {
+			return Err(anyhow!(
+				"Refusing to run: detected Aura key {} is not present in this node's keystore (author_hasKey=false).",
+				key.author_hex
+			));
+		}
+		eprintln!(
+			"{}: {}",
+			colors.error(i18n.pick(
+				"WARNING: Aura key is not present in this node's keystore (author_hasKey=false)",
+				"警告: このノードのキーストアに Aura キーがありません (author_hasKey=false)"
+			)),
+			key.author_hex
+		);
 	}
 
-	let epoch_size = common.epoch_size as u64;
+	let epoch_size = resolve_epoch_size(&api, common.epoch_size, &i18n, &colors);
 
 	if common.output_json {
-		if common.current == common.next {
-			return Err(anyhow!("--output-json requires exactly one of --current or --next"));
+		let modes = [common.current, common.next, common.epochs.is_some(), common.all_known];
+		if modes.iter().filter(|m| **m).count() != 1 {
+			return Err(anyhow!("--output-json requires exactly one of --current, --next, --epochs or --all-known"));
 		}
 
 		let auths = fetch_authorities(&api)?;
-		let slot_dur_ms: u64 = api
-			.get_constant("Aura", "SlotDuration")
-			.map_err(|e| anyhow!("{e:?}"))?;
-		let ts_ms: u64 = api
-			.get_storage("Timestamp", "Now", None)
-			.map_err(|e| anyhow!("{e:?}"))?
-			.unwrap_or(0);
-		let best_hash = api
-			.get_block_hash(None)
-			.map_err(|e| anyhow!("{e:?}"))?
-			.ok_or_else(|| anyhow!("no best head"))?;
-		let best_header = api
-			.get_header(Some(best_hash))
-			.map_err(|e| anyhow!("{e:?}"))?
-			.ok_or_else(|| anyhow!("no best header"))?;
+		let slot_dur_ms = api.slot_duration_ms()?;
+		let ts_ms = api.timestamp_ms(None)?.unwrap_or(0);
+		let best_hash = api.best_hash()?.ok_or_else(|| anyhow!("no best head"))?;
+		let best_header = api.header(best_hash)?.ok_or_else(|| anyhow!("no best header"))?;
 		let latest_slot =
 			aura_slot_from_header(&best_header).unwrap_or_else(|| ts_ms / slot_dur_ms.max(1));
 		let epoch_idx = latest_slot / epoch_size;
+		warn_on_epoch_mismatch(&api, epoch_idx, &i18n, &colors);
+
+		// `(epoch, committee, source)` in output order; without a committee the Aura round-robin is used.
+		let mut epochs: Vec<(u64, Option<CommitteeSchedule>, &str)> = Vec::new();
+		if !common.next {
+			epochs.push(match api.current_committee() {
+				Ok(Some((committee_epoch, committee))) if committee_epoch == epoch_idx => {
+					(epoch_idx, Some(committee), COMMITTEE_SOURCE_CURRENT)
+				}
+				_ => (epoch_idx, None, SCHEDULE_SOURCE_AURA),
+			});
+		}
+		if common.next || common.all_known || common.epochs.is_some_and(|n| n > 1) {
+			match api.next_committee() {
+				Ok(Some((next_epoch, schedule))) if common.next || next_epoch > epoch_idx => {
+					epochs.push((next_epoch, Some(schedule), COMMITTEE_SOURCE_NEXT));
+				}
+				// Until `NextCommittee` is set, the current set's round-robin is the best guess.
+				_ if !common.all_known => epochs.push((epoch_idx + 1, None, SCHEDULE_SOURCE_AURA)),
+				_ => {}
+			}
+		}
+		if let Some(n) = common.epochs
+			&& n as usize > epochs.len()
+		{
+			eprintln!(
+				"{}",
+				colors.error(format!(
+					"{}: {}/{n}",
+					i18n.pick(
+						"WARNING: committees after the next epoch are not on chain yet; epochs in the output",
+						"警告: 次エポックより先の委員会はまだチェーン上にありません。出力したエポック数",
+					),
+					epochs.len()
+				))
+			);
+		}
 
-		let (epoch, schedule_slots): (u64, Vec<u64>) = if common.next {
-			let next_start_slot = (epoch_idx + 1) * epoch_size;
-			match fetch_committee_info(&api, "SessionCommitteeManagement", "NextCommittee") {
-				Ok(Some((next_epoch, schedule))) => {
-					let mut my: Vec<u64> = Vec::new();
-					for (i, (aura, _grandpa)) in schedule.iter().enumerate() {
-						if aura == &author_bytes {
-							my.push(next_start_slot + (i as u64));
+		// `(slot, epoch, author, source)` of every monitored key, in slot order.
+		let mut schedule_slots: Vec<(u64, u64, &str, &str)> = Vec::new();
+		for (epoch, committee, source) in &epochs {
+			let start_slot = epoch * epoch_size;
+			for key in &keys {
+				let aura_slots = compute_my_slots(&auths, &key.author_bytes, start_slot, epoch_size);
+				let slots = match committee.as_ref() {
+					Some(committee) => {
+						let slots = committee_slots(committee, &key.author_bytes, start_slot, epoch_size);
+						if *source == COMMITTEE_SOURCE_CURRENT {
+							report_schedule_divergence(&slots, &aura_slots, &i18n, &colors);
 						}
+						slots
 					}
-					(next_epoch, my)
-				}
-				_ => {
-					let my = compute_my_slots(&auths, &author_bytes, next_start_slot, epoch_size);
-					(epoch_idx + 1, my)
-				}
+					None => aura_slots,
+				};
+				schedule_slots.extend(slots.into_iter().map(|slot| (slot, *epoch, key.author_hex.as_str(), *source)));
 			}
+		}
+		schedule_slots.sort();
+
+		let v = if common.current || common.next {
+			let schedule = schedule_slots
+				.iter()
+				.map(|(slot, _, author, _)| {
+					let ts = planned_ts_ms(*slot, latest_slot, ts_ms, slot_dur_ms);
+					serde_json::json!({
+						"slot": slot,
+						"date": format_ts(ts, &out_tz),
+						"author": author,
+					})
+				})
+				.collect::<Vec<_>>();
+			serde_json::json!({
+				"epoch": epochs[0].0,
+				"schedule": schedule,
+			})
 		} else {
-			let start_slot = epoch_idx * epoch_size;
-			let my = compute_my_slots(&auths, &author_bytes, start_slot, epoch_size);
-			(epoch_idx, my)
-		};
-
-		let schedule = schedule_slots
-			.iter()
-			.map(|slot| {
-				let delta_slots = *slot as i64 - latest_slot as i64;
-				let ts = ts_ms as i64 + (delta_slots * slot_dur_ms as i64);
-				serde_json::json!({
-					"slot": slot,
-					"date": format_ts(ts, &out_tz),
+			let summary = epochs
+				.iter()
+				.map(|(epoch, _, source)| {
+					serde_json::json!({
+						"epoch": epoch,
+						"start_slot": epoch * epoch_size,
+						"end_slot": (epoch + 1) * epoch_size - 1,
+						"source": source,
+						"slots": schedule_slots.iter().filter(|s| s.1 == *epoch).count(),
+					})
+				})
+				.collect::<Vec<_>>();
+			let schedule = schedule_slots
+				.iter()
+				.map(|(slot, epoch, author, source)| {
+					let ts = planned_ts_ms(*slot, latest_slot, ts_ms, slot_dur_ms);
+					serde_json::json!({
+						"epoch": epoch,
+						"slot": slot,
+						"slot_in_epoch": slot - epoch * epoch_size,
+						"planned_time_utc": format_ts(ts, &utc_tz),
+						"date": format_ts(ts, &out_tz),
+						"author": author,
+						"source": source,
+					})
 				})
+				.collect::<Vec<_>>();
+			serde_json::json!({
+				"epochs": summary,
+				"schedule": schedule,
 			})
-			.collect::<Vec<_>>();
-
-		let v = serde_json::json!({
-			"epoch": epoch,
-			"schedule": schedule,
-		});
+		};
 		println!("{}", serde_json::to_string_pretty(&v)?);
 		return Ok(());
 	}
@@ -1845,50 +1356,87 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 	} else {
 		let conn = Connection::open(&common.db)?;
 		ensure_db(&conn)?;
+		if !multi_key {
+			db_claim_legacy_rows(&conn, &keys[0].author_hex)?;
+		}
 		Some(conn)
 	};
 
-	let mut prev_hash: Option<[u8; 32]> = None;
-	let mut prev_len: usize = 0;
-	let mut prev_author_present: Option<bool> = None;
-	let mut prev_my_schedule_hash: Option<[u8; 32]> = None;
-	let mut prev_schedule_view_hash: Option<[u8; 32]> = None;
-	let mut prev_epoch: Option<u64> = None;
+	let mut views: Vec<KeyView> = keys.iter().map(|_| KeyView::default()).collect();
 	let mut cached_epoch_rows: Option<Vec<(String, String)>> = None;
-	let mut current_my_slots: Vec<u64> = Vec::new();
+	// Latest Ariadne result per key: (stake lovelace, registration valid).
+	let mut registrations: Vec<Option<(u128, bool)>> = vec![None; keys.len()];
+	let metrics: Option<SharedMetrics> = match common.metrics_addr.as_deref() {
+		Some(addr) => {
+			let m = SharedMetrics::default();
+			serve_metrics(addr, m.clone())?;
+			Some(m)
+		}
+		None => None,
+	};
+	// Last finalized head and when it was first seen, for `mblog_finalized_head_age_seconds`.
+	let mut finalized_advance: (u64, std::time::Instant) = (0, std::time::Instant::now());
+	let notifier = match common.webhook_url.as_deref() {
+		Some(url) => {
+			let template = match common.webhook_template.as_deref() {
+				Some(t) => Some(match t.strip_prefix('@') {
+					Some(path) => std::fs::read_to_string(path)
+						.map_err(|e| anyhow!("failed to read --webhook-template '{path}': {e}"))?,
+					None => t.to_string(),
+				}),
+				None => None,
+			};
+			Some(Notifier::spawn(WebhookConfig {
+				url: url.to_string(),
+				template,
+				retries: common.webhook_retries,
+			})?)
+		}
+		None => None,
+	};
+	let mut reminders = if common.remind.is_empty() {
+		None
+	} else {
+		let leads = common.remind.iter().map(|s| parse_lead_time(s)).collect::<anyhow::Result<Vec<_>>>()?;
+		Some(Reminders::new(leads))
+	};
 	let mut pending_next_committee_print: bool = true; // also print on first render
 	let mut next_preview_printed: bool = false;
 	let mut waiting_notice_printed: bool = false;
-	let mut waiting_progress_tick: u64 = 0;
-	// Do not backfill from genesis on startup; initialize cursors from the current chain state.
-	let initial_best_hash: Option<sp_core::H256> = api
-		.get_block_hash(None)
-		.map_err(|e| anyhow!("{e:?}"))?;
-	let mut last_best_number: u64 = match initial_best_hash {
-		Some(h) => api
-			.get_header(Some(h))
-			.map_err(|e| anyhow!("{e:?}"))?
-			.map(|hdr| hdr.number.into())
-			.unwrap_or(0),
-		None => 0,
-	};
-	let mut last_finalized_number: u64 = match api
-		.get_finalized_head()
-		.map_err(|e| anyhow!("{e:?}"))?
+	let mut current_committee: Option<(u64, CommitteeSchedule)> = None;
+	// `NextCommittee` whose slots were last stored, so unchanged reads are not written again.
+	let mut next_committee_stored: Option<(u64, CommitteeSchedule)> = None;
+	let mut committee_check_pending: bool = false;
+	// `NextCommittee` can only change with a new best block; finalized-only wake-ups skip the read.
+	let mut best_head_moved: bool = true;
+	// Subscribe before reading the initial state so no head between the two is lost.
+	let mut heads = if common.watch { Some(api.subscribe_heads()?) } else { None };
+	// Never backfill from genesis: start from the current chain state, or in watch mode from the cursors
+	// stored by the previous run (bounded by --max-backfill). A one-shot run neither replays nor moves them.
+	let mut watcher = Watcher::new(&api, keys.iter().map(|k| k.author_bytes).collect(), epoch_size)?;
+	watcher.set_record_chain(common.record_chain);
+	if common.watch
+		&& let Some(c) = conn.as_ref()
 	{
-		Some(h) => api
-			.get_header(Some(h))
-			.map_err(|e| anyhow!("{e:?}"))?
-			.map(|hdr| hdr.number.into())
-			.unwrap_or(0),
-		None => 0,
-	};
+		let backfill = watcher.resume(c, common.max_backfill)?;
+		if backfill > 0 {
+			println!(
+				"{}",
+				colors.dim(format!(
+					"{}: {backfill} {}",
+					i18n.pick("Backfilling since last run", "前回終了時からのバックフィル"),
+					i18n.pick("blocks", "ブロック")
+				))
+			);
+		}
+	}
+	let mut finalized_number = api.finalized_number()?;
 
 	if common.debug_metadata {
 		println!();
 		println!("Session metadata / state diagnostics");
 		println!("----------------------------------");
-		let rows = debug_session_metadata(&api, &author_bytes);
+		let rows = debug_session_metadata(&api, &keys[0].author_bytes);
 		print_kv_table(&rows);
 		println!();
 	}
@@ -1940,14 +1488,12 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 		}
 	}
 
+	let mut head = watcher.observe_head(&api)?;
+	warn_on_epoch_mismatch(&api, head.epoch, &i18n, &colors);
 	loop {
-		let auths = fetch_authorities(&api)?;
-		let current_hash = hash_authorities(&auths);
-		let current_hash_hex = hex32(current_hash);
-
-		let changed = prev_hash.is_none()
-			|| prev_hash.unwrap() != current_hash
-			|| prev_len != auths.len();
+		let auths = &head.auths;
+		let current_hash_hex = hex32(head.auth_hash);
+		let changed = head.auths_changed;
 
 		let mut screen_cleared = false;
 		if live_update && changed {
@@ -1963,7 +1509,7 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 				println!();
 			}
 			waiting_notice_printed = false;
-				if prev_hash.is_some() {
+				if let Some(prev_len) = head.prev_auths_len {
 					println!();
 					println!("--------------------------------------------------------------");
 					println!(
@@ -1973,38 +1519,26 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 						auths.len()
 					);
 				}
-			prev_hash = Some(current_hash);
-			prev_len = auths.len();
 		}
 
-		let slot_dur_ms: u64 = api
-			.get_constant("Aura", "SlotDuration")
-			.map_err(|e| anyhow!("{e:?}"))?;
-		let ts_ms: u64 = api
-			.get_storage("Timestamp", "Now", None)
-			.map_err(|e| anyhow!("{e:?}"))?
-			.unwrap_or(0);
-		let best_hash = api
-			.get_block_hash(None)
-			.map_err(|e| anyhow!("{e:?}"))?
-			.ok_or_else(|| anyhow!("no best head"))?;
-		let best_header = api
-			.get_header(Some(best_hash))
-			.map_err(|e| anyhow!("{e:?}"))?
-			.ok_or_else(|| anyhow!("no best header"))?;
-
-		// Prefer the real Aura slot from the block digest; timestamp/slot_duration is only a fallback.
-		let latest_slot = aura_slot_from_header(&best_header).unwrap_or_else(|| ts_ms / slot_dur_ms);
-		let best_number: u64 = best_header.number.into();
-		let epoch_idx = latest_slot / epoch_size;
-		let start_slot = epoch_idx * epoch_size;
+		let slot_dur_ms = head.slot_dur_ms;
+		let ts_ms = head.ts_ms;
+		let latest_slot = head.latest_slot;
+		let epoch_idx = head.epoch;
+		let start_slot = head.start_slot;
 		let slots_to_scan = epoch_size;
-		let epoch_end_slot = start_slot + epoch_size.saturating_sub(1);
-
-		let epoch_switched = prev_epoch.is_none() || prev_epoch.unwrap() != epoch_idx;
+		let epoch_end_slot = head.end_slot;
+		let epoch_switched = head.epoch_switched;
 		if epoch_switched {
 			pending_next_committee_print = true;
 			next_preview_printed = false;
+			for view in views.iter_mut() {
+				view.next_planned.clear();
+			}
+		}
+		if changed || epoch_switched {
+			current_committee = api.current_committee().ok().flatten();
+			committee_check_pending = true;
 		}
 
 		if live_update && (changed || epoch_switched) && !screen_cleared {
@@ -2034,17 +1568,33 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 				colors.dim(paren)
 			);
 
-			if let Some(ref c) = conn {
-				db_upsert_epoch_info(c, epoch_idx, start_slot, epoch_end_slot, &current_hash_hex, auths.len())?;
+			if let Some(ref mut c) = conn {
+				for key in &keys {
+					db_upsert_epoch_info(
+						c,
+						epoch_idx,
+						&key.author_hex,
+						start_slot,
+						epoch_end_slot,
+						&current_hash_hex,
+						auths.len(),
+					)?;
+				}
+				let auth_hexes: Vec<String> = auths.iter().map(|a| hex0x(a.as_ref())).collect();
+				db_replace_epoch_authorities(c, epoch_idx, &auth_hexes)?;
+				if let Some((committee_epoch, committee)) = current_committee.as_ref() {
+					db_replace_epoch_committee(c, *committee_epoch, COMMITTEE_SOURCE_CURRENT, committee)?;
+				}
 			}
 
 			let mut rows: Vec<(String, String)> = Vec::new();
-			rows.push(("author".to_string(), colors.author(&author_hex)));
 
 				// NOTE: `--show-next-active-set` is intentionally disabled for now because the target
 				// runtime does not expose the necessary Session storage items.
 
-			if let (Some(sc), Some(http)) = (sidechain_pubkey.as_deref(), ariadne_client.as_ref()) {
+			let main_epoch = if keys.iter().any(|k| k.sidechain_pubkey.is_some())
+				&& let Some(http) = ariadne_client.as_ref()
+			{
 				let status = jsonrpc_http_call(
 					http,
 					&common.ariadne_endpoint,
@@ -2058,10 +1608,41 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 					.and_then(|v| v.get("mainchain"))
 					.and_then(|v| v.get("epoch"))
 					.and_then(|v| v.as_u64());
+				if main_epoch.is_none() {
+					eprintln!(
+						"{}",
+						colors.dim(i18n.pick(
+							"registration check skipped (failed to read mainchain epoch)",
+							"登録チェックをスキップしました（mainchain epoch を取得できません）",
+						))
+					);
+				}
+				main_epoch
+			} else {
+				None
+			};
+
+			for (key, registration) in keys.iter().zip(registrations.iter_mut()) {
+				rows.push(("author".to_string(), colors.author(&key.author_hex)));
 
-				if let Some(main_epoch) = main_epoch {
+				if let (Some(sc), Some(http), Some(main_epoch)) =
+					(key.sidechain_pubkey.as_deref(), ariadne_client.as_ref(), main_epoch)
+				{
 					match fetch_registration_status(http, &common.ariadne_endpoint, sc, main_epoch) {
 						Ok((lovelace, is_valid)) => {
+							if let (Some(n), Some((_, true)), false) = (notifier.as_ref(), *registration, is_valid) {
+								n.notify(Event::new(
+									EventKind::RegistrationInvalid,
+									&key.author_hex,
+									epoch_idx,
+									format!(
+										"{}: {} (epoch {epoch_idx})",
+										i18n.pick("Registration is no longer valid", "登録が無効になりました"),
+										key.author_hex
+									),
+								));
+							}
+							*registration = Some((lovelace, is_valid));
 							let ada = format_ada_from_lovelace(lovelace);
 							rows.push((
 								i18n.pick("ADA Stake", "ADA委任量").to_string(),
@@ -2085,294 +1666,392 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 							);
 						}
 					}
-				} else {
+				}
+			}
+
+			cached_epoch_rows = Some(rows.clone());
+			print_kv_table(&rows);
+			println!();
+		}
+
+		let print_next_preview = pending_next_committee_print;
+		pending_next_committee_print = false;
+		for (key, view) in keys.iter().zip(views.iter_mut()) {
+			let author_bytes = key.author_bytes;
+			// (progress is rendered in the waiting section, so it appears under the waiting line)
+			let author_present = author_in_authorities(&author_bytes, auths);
+			let author_present_changed = view.present != Some(author_present);
+			if let Some(ref n) = notifier
+				&& !author_present
+				&& view.present == Some(true)
+			{
+				n.notify(Event::new(
+					EventKind::LeftCommittee,
+					&key.author_hex,
+					epoch_idx,
+					format!(
+						"{}: {} (epoch {epoch_idx})",
+						i18n.pick("Left the Aura authority set", "Aura 権限セットから外れました"),
+						key.author_hex
+					),
+				));
+			}
+			view.present = Some(author_present);
+
+			if !author_present {
+				view.my_slots.clear();
+				view.view_hash = None;
+				view.planned.clear();
+				if changed || author_present_changed || epoch_switched {
+					if let Some(ref c) = conn {
+						db_drop_stale_next_schedule(c, epoch_idx, &key.author_hex)?;
+					}
+					if multi_key {
+						println!("author: {}", colors.author(&key.author_hex));
+					}
 					eprintln!(
 						"{}",
-						colors.dim(i18n.pick(
-							"registration check skipped (failed to read mainchain epoch)",
-							"登録チェックをスキップしました（mainchain epoch を取得できません）",
+						colors.error(i18n.pick(
+							"Nothing scheduled for this session.",
+							"このセッションにスケジュールはありません",
 						))
 					);
 				}
+				if print_next_preview {
+					println!();
+					// Next epoch committee preview (your assigned slots only)
+					let next_start_slot = (epoch_idx + 1) * epoch_size;
+					print_next_committee_for_author(
+						&i18n,
+						&colors,
+						&api,
+						&author_bytes,
+						latest_slot,
+						ts_ms,
+						slot_dur_ms,
+						next_start_slot,
+						epoch_size,
+						&out_tz,
+						&utc_tz,
+					);
+				}
+				continue;
 			}
 
-			cached_epoch_rows = Some(rows.clone());
-			print_kv_table(&rows);
-			println!();
+			// The committee storage is what the runtime enforces; the Aura round-robin is the cross-check.
+			let aura_slots = compute_my_slots(auths, &author_bytes, start_slot, slots_to_scan);
+			let (my_slots, schedule_source) = match current_committee.as_ref().filter(|(e, _)| *e == epoch_idx) {
+				Some((_, committee)) => {
+					let slots = committee_slots(committee, &author_bytes, start_slot, slots_to_scan);
+					if committee_check_pending {
+						report_schedule_divergence(&slots, &aura_slots, &i18n, &colors);
+					}
+					(slots, COMMITTEE_SOURCE_CURRENT)
+				}
+				None => (aura_slots, SCHEDULE_SOURCE_AURA),
+			};
+			view.my_slots = my_slots;
+			let my_hash = schedule_hash(&view.my_slots);
+			let my_changed = view.schedule_hash != Some(my_hash);
+
+			if my_changed || epoch_switched {
+				if !live_update && is_tty && waiting_notice_printed {
+					println!();
+				}
+				waiting_notice_printed = false;
+				view.schedule_hash = Some(my_hash);
+
+				let planned: Vec<(u64, String)> = view
+					.my_slots
+					.iter()
+					.map(|slot| {
+						let ts = planned_ts_ms(*slot, latest_slot, ts_ms, slot_dur_ms);
+						(*slot, format_ts(ts, &utc_tz))
+					})
+					.collect();
+
+				if let Some(ref mut c) = conn {
+					db_insert_schedule(c, epoch_idx, &key.author_hex, &planned, schedule_source)?;
+					let dropped = db_drop_stale_next_schedule(c, epoch_idx, &key.author_hex)?;
+					if dropped > 0 {
+						eprintln!(
+							"{}",
+							colors.error(format!(
+								"{}: {dropped}",
+								i18n.pick(
+									"WARNING: slots planned from NextCommittee that are not in the epoch's schedule (removed)",
+									"警告: NextCommittee から計画したがエポックのスケジュールにないスロット（削除しました）"
+								)
+							))
+						);
+					}
+				}
+
+				let schedule_rows = load_schedule_rows(conn.as_ref(), &view.my_slots, &planned)?;
+				view.view_hash = Some(schedule_rows_hash(&schedule_rows));
+				view.planned = schedule_rows.iter().map(|r| (r.slot, r.planned_time_utc.clone())).collect();
+
+				if let Some(ref n) = notifier
+					&& let Some((first_slot, first_time)) = planned.first()
+				{
+					for row in schedule_rows.iter().filter(|r| matches!(r.status.as_str(), "schedule" | "mint")) {
+						view.tracked.entry(row.slot).or_insert_with(|| row.status.clone());
+					}
+					let mut event = Event::new(
+						EventKind::Schedule,
+						&key.author_hex,
+						epoch_idx,
+						format!(
+							"{}: epoch {epoch_idx}, {} slot(s), first slot {first_slot} at {first_time}",
+							i18n.pick("Slots scheduled", "スロットが割り当てられました"),
+							planned.len()
+						),
+					);
+					event.slot = Some(*first_slot);
+					n.notify(event);
+				}
+
+				if multi_key {
+					println!("author: {}", colors.author(&key.author_hex));
+				}
+				print_schedule_rows(&i18n, &colors, &out_tz, &utc_tz, epoch_idx, &schedule_rows);
+
+				if print_next_preview {
+					println!();
+					// Next epoch committee preview (your assigned slots only)
+					let next_start_slot = (epoch_idx + 1) * epoch_size;
+					print_next_committee_for_author(
+						&i18n,
+						&colors,
+						&api,
+						&author_bytes,
+						latest_slot,
+						ts_ms,
+						slot_dur_ms,
+						next_start_slot,
+						epoch_size,
+						&out_tz,
+						&utc_tz,
+					);
+					next_preview_printed = true;
+				}
+				if multi_key {
+					println!();
+				}
+			}
+		}
+		committee_check_pending = false;
+
+		// Store the next epoch's slots as soon as `NextCommittee` is readable; the rows are re-sourced (or
+		// dropped) once that epoch starts and its actual schedule is stored above.
+		if best_head_moved
+			&& let Ok(Some((next_epoch, committee))) = api.next_committee()
+			&& next_epoch > epoch_idx
+			&& !matches!(&next_committee_stored, Some((e, c)) if *e == next_epoch && *c == committee)
+		{
+			let next_start_slot = next_epoch * epoch_size;
+			for (key, view) in keys.iter().zip(views.iter_mut()) {
+				view.next_planned = committee_slots(&committee, &key.author_bytes, next_start_slot, epoch_size)
+					.into_iter()
+					.map(|slot| (slot, format_ts(planned_ts_ms(slot, latest_slot, ts_ms, slot_dur_ms), &utc_tz)))
+					.collect();
+				if let Some(ref mut c) = conn {
+					db_insert_schedule(c, next_epoch, &key.author_hex, &view.next_planned, COMMITTEE_SOURCE_NEXT)?;
+				}
+			}
+			if let Some(ref mut c) = conn {
+				db_replace_epoch_committee(c, next_epoch, COMMITTEE_SOURCE_NEXT, &committee)?;
+			}
+			next_committee_stored = Some((next_epoch, committee));
 		}
 
-				// (progress is rendered in the waiting section, so it appears under the waiting line)
-		let author_present = author_in_authorities(&author_bytes, &auths);
-		let author_present_changed =
-			prev_author_present.is_none() || prev_author_present.unwrap() != author_present;
-		prev_author_present = Some(author_present);
+			if let Err(e) = watcher
+				.scan_minted(&api, conn.as_ref(), &head)
+				.and_then(|()| watcher.scan_finalized_to(&api, conn.as_ref(), finalized_number))
+			{
+				// A locked or full DB does not get better by reconnecting to the node.
+				if !common.watch || is_store_error(&e) {
+					return Err(e);
+				}
+				let rx;
+				(api, rx, finalized_number, head) = reconnect(&common.ws, &mut watcher, &colors, &i18n, e);
+				heads = Some(rx);
+				continue;
+			}
+
+		// Webhook: status transitions of the tracked slots (kept across the epoch switch until final).
+		if let (Some(n), Some(c)) = (notifier.as_ref(), conn.as_ref()) {
+			for (key, view) in keys.iter().zip(views.iter_mut()) {
+				let slots: Vec<u64> = view.tracked.keys().copied().collect();
+				for row in db_fetch_schedule_rows(c, &slots)? {
+					if view.tracked.get(&row.slot) == Some(&row.status) {
+						continue;
+					}
+					if let Some(kind) = EventKind::from_block_status(&row.status) {
+						let block = row.block_number.map(|b| format!(", block {b}")).unwrap_or_default();
+						let mut event = Event::new(
+							kind,
+							&key.author_hex,
+							row.slot / epoch_size,
+							format!("{}: slot {}{block}", slot_event_label(kind, &i18n), row.slot),
+						);
+						event.slot = Some(row.slot);
+						event.block_number = row.block_number;
+						event.block_hash = row.block_hash.clone();
+						n.notify(event);
+					}
+					if matches!(row.status.as_str(), "schedule" | "mint") {
+						view.tracked.insert(row.slot, row.status);
+					} else {
+						view.tracked.remove(&row.slot);
+					}
+				}
+			}
+		}
 
-				if !author_present {
-					current_my_slots.clear();
-					prev_schedule_view_hash = None;
-						if changed || author_present_changed || prev_epoch.is_none() || prev_epoch.unwrap() != epoch_idx {
-							eprintln!(
-								"{}",
-								colors.error(i18n.pick(
-									"Nothing scheduled for this session.",
-									"このセッションにスケジュールはありません",
-								))
-							);
+		// Watch SQLite schedule status changes and refresh the displayed schedules.
+		if let Some(ref c) = conn {
+			let mut refreshed: Vec<(usize, Vec<ScheduleRow>)> = Vec::new();
+			for (i, view) in views.iter_mut().enumerate() {
+				if view.present != Some(true) || view.my_slots.is_empty() {
+					continue;
+				}
+				let schedule_rows = db_fetch_schedule_rows(c, &view.my_slots)?;
+				let new_hash = schedule_rows_hash(&schedule_rows);
+				if view.view_hash != Some(new_hash) {
+					view.view_hash = Some(new_hash);
+					refreshed.push((i, schedule_rows));
+				}
+			}
+			if !refreshed.is_empty() {
+				waiting_notice_printed = false;
+				if live_update {
+					print!("\r\x1b[2K\x1b[2J\x1b[H{banner}");
+					let _ = std::io::stdout().flush();
+
+					let paren = format!(
+						"({}:{} / {}:{})",
+						i18n.pick("start_slot", "開始スロット"),
+						start_slot,
+						i18n.pick("end_slot", "終了スロット"),
+						epoch_end_slot
+					);
+					println!(
+						"{}:{} {}",
+						i18n.pick("epoch", "エポック"),
+						colors.epoch(epoch_idx.to_string()),
+						colors.dim(paren)
+					);
+					if let Some(ref rows) = cached_epoch_rows {
+						print_kv_table(rows);
+						println!();
+					}
+					// The screen was cleared, so every key's schedule is printed again.
+					refreshed.clear();
+					for (i, view) in views.iter().enumerate() {
+						if view.present == Some(true) && !view.my_slots.is_empty() {
+							refreshed.push((i, db_fetch_schedule_rows(c, &view.my_slots)?));
 						}
-					prev_epoch = Some(epoch_idx);
-					if pending_next_committee_print {
-						pending_next_committee_print = false;
+					}
+				}
+
+				for (i, schedule_rows) in &refreshed {
+					let key = &keys[*i];
+					if multi_key {
+						println!("author: {}", colors.author(&key.author_hex));
+					}
+					print_schedule_rows(&i18n, &colors, &out_tz, &utc_tz, epoch_idx, schedule_rows);
+
+					if live_update && next_preview_printed {
 						println!();
-						// Next epoch committee preview (your assigned slots only)
 						let next_start_slot = (epoch_idx + 1) * epoch_size;
 						print_next_committee_for_author(
 							&i18n,
 							&colors,
 							&api,
-							&author_bytes,
+							&key.author_bytes,
 							latest_slot,
 							ts_ms,
 							slot_dur_ms,
 							next_start_slot,
+							epoch_size,
 							&out_tz,
 							&utc_tz,
 						);
 					}
-				} else {
-					current_my_slots =
-						compute_my_slots(&auths, &author_bytes, start_slot, slots_to_scan);
-					let my_hash = schedule_hash(&current_my_slots);
-					let my_changed =
-						prev_my_schedule_hash.is_none() || prev_my_schedule_hash.unwrap() != my_hash;
-					let epoch_changed = epoch_switched;
-
-					if my_changed || epoch_changed {
-						if !live_update && is_tty && waiting_notice_printed {
-							println!();
-						}
-						waiting_notice_printed = false;
-						prev_my_schedule_hash = Some(my_hash);
-						prev_epoch = Some(epoch_idx);
-
-						let planned: Vec<(u64, String)> = current_my_slots
-							.iter()
-							.map(|slot| {
-								let ts = ts_ms as i64
-									+ ((*slot as i64 - latest_slot as i64) * slot_dur_ms as i64);
-								(*slot, format_ts(ts, &utc_tz))
-							})
-							.collect();
-
-						if let Some(ref mut c) = conn {
-							db_insert_schedule(c, epoch_idx, &planned)?;
-						}
-
-						let planned_by_slot: HashMap<u64, String> =
-							planned.iter().map(|(s, t)| (*s, t.clone())).collect();
-
-						let schedule_rows: Vec<ScheduleRow> = if let Some(ref c) = conn {
-							let fetched = db_fetch_schedule_rows(c, &current_my_slots)?;
-							let mut by_slot: HashMap<u64, ScheduleRow> = HashMap::new();
-							for r in fetched {
-								by_slot.insert(r.slot, r);
-							}
-							current_my_slots
-								.iter()
-								.map(|slot| {
-									by_slot.get(slot).cloned().unwrap_or_else(|| ScheduleRow {
-										slot: *slot,
-										planned_time_utc: planned_by_slot
-											.get(slot)
-											.cloned()
-											.unwrap_or_else(|| "-".to_string()),
-										status: "schedule".to_string(),
-										block_number: None,
-										block_hash: None,
-										produced_time_utc: None,
-									})
-								})
-								.collect()
-						} else {
-							current_my_slots
-								.iter()
-								.map(|slot| ScheduleRow {
-									slot: *slot,
-									planned_time_utc: planned_by_slot
-										.get(slot)
-										.cloned()
-										.unwrap_or_else(|| "-".to_string()),
-									status: "schedule".to_string(),
-									block_number: None,
-									block_hash: None,
-									produced_time_utc: None,
-								})
-								.collect()
-						};
-
-						prev_schedule_view_hash = Some(schedule_rows_hash(&schedule_rows));
-
-						println!(
-							"{}: {}",
-							i18n.pick("Current epoch Schedule", "現在のエポックのスケジュール"),
-							colors.epoch(epoch_idx.to_string())
-						);
-						println!("-------------------------");
-								for (idx, row) in schedule_rows.iter().enumerate() {
-									let dt_utc = parse_rfc3339_utc(&row.planned_time_utc);
-									let out_ts = dt_utc
-										.map(|dt| colors.time(format_dt(dt, &out_tz)))
-								.unwrap_or_else(|| "-".to_string());
-							let utc_ts = dt_utc
-								.map(|dt| format_dt(dt, &utc_tz))
-								.unwrap_or_else(|| "-".to_string());
-
-							let status = status_tag(&colors, &row.status);
-							println!(
-								"#{idx1} slot {}: {} (UTC {}){}",
-								colors.slot(row.slot.to_string()),
-								out_ts,
-								colors.dim(utc_ts),
-								status,
-								idx1 = idx + 1
-							);
-						}
-						println!("{}={}", i18n.pick("Total", "合計"), schedule_rows.len());
-
-						if pending_next_committee_print {
-							pending_next_committee_print = false;
-							println!();
-							// Next epoch committee preview (your assigned slots only)
-							let next_start_slot = (epoch_idx + 1) * epoch_size;
-							print_next_committee_for_author(
-								&i18n,
-								&colors,
-								&api,
-								&author_bytes,
-								latest_slot,
-								ts_ms,
-								slot_dur_ms,
-								next_start_slot,
-								&out_tz,
-								&utc_tz,
-							);
-							next_preview_printed = true;
-						}
-					}
-				}
-
-			// Mint detection: scan new best blocks since last check and mark blocks produced by this author.
-			// This avoids missing mint events when the tool sleeps between polls.
-			if best_number > last_best_number {
-				for n in (last_best_number + 1)..=best_number {
-					let bn_u32: u32 = n.try_into().map_err(|_| anyhow!("best block number {n} does not fit u32"))?;
-					let Some(h) = api.get_block_hash(Some(bn_u32)).map_err(|e| anyhow!("{e:?}"))? else {
-						continue;
-					};
-					let Some(hdr) = api.get_header(Some(h)).map_err(|e| anyhow!("{e:?}"))? else {
-						continue;
-					};
-					let Some(slot) = aura_slot_from_header(&hdr) else {
-						continue;
-					};
-					let auths_for_slot = authorities_at(&api, hdr.parent_hash).unwrap_or_else(|_| auths.clone());
-					if auths_for_slot.is_empty() {
-						continue;
-					}
-					let expected = &auths_for_slot[(slot as usize) % auths_for_slot.len()];
-					let expected_bytes: &[u8] = expected.as_ref();
-					if expected_bytes != author_bytes.as_slice() {
-						continue;
-					}
-					if let Some(ref c) = conn {
-						let block_hash_str = format!("{h:?}");
-						let produced_time_utc = block_time_utc(&api, h);
-						db_upsert_minted_block(c, slot, slot / epoch_size, n, &block_hash_str, &produced_time_utc)?;
+					if multi_key {
+						println!();
 					}
 				}
-				last_best_number = best_number;
 			}
+		}
 
-			// Finality: scan new finalized blocks since last check and update scheduled slots.
-			let _ = scan_new_finalized_blocks(&api, conn.as_ref(), &mut last_finalized_number)?;
-
-				// Watch SQLite schedule status changes and refresh the displayed schedule.
-				if author_present && !current_my_slots.is_empty() {
-					if let Some(ref c) = conn {
-						let schedule_rows = db_fetch_schedule_rows(c, &current_my_slots)?;
-						let new_hash = schedule_rows_hash(&schedule_rows);
-						let changed = prev_schedule_view_hash.map(|h| h != new_hash).unwrap_or(true);
-						if changed {
-							prev_schedule_view_hash = Some(new_hash);
-							waiting_notice_printed = false;
-							if live_update {
-								print!("\r\x1b[2K\x1b[2J\x1b[H{banner}");
-								let _ = std::io::stdout().flush();
-
-								let paren = format!(
-									"({}:{} / {}:{})",
-									i18n.pick("start_slot", "開始スロット"),
-									start_slot,
-									i18n.pick("end_slot", "終了スロット"),
-									epoch_end_slot
-								);
-								println!(
-									"{}:{} {}",
-									i18n.pick("epoch", "エポック"),
-									colors.epoch(epoch_idx.to_string()),
-									colors.dim(paren)
-								);
-								if let Some(ref rows) = cached_epoch_rows {
-									print_kv_table(rows);
-									println!();
-								}
-							}
+		if let Some(ref mut r) = reminders {
+			fire_reminders(
+				r,
+				&keys,
+				&views,
+				common.remind_command.as_deref(),
+				notifier.as_ref(),
+				epoch_size,
+				&out_tz,
+				&i18n,
+				&colors,
+			);
+		}
 
-							println!(
-								"{}: {}",
-								i18n.pick("Current epoch Schedule", "現在のエポックのスケジュール"),
-								colors.epoch(epoch_idx.to_string())
-							);
-							println!("-------------------------");
-							for (idx, row) in schedule_rows.iter().enumerate() {
-								let dt_utc = parse_rfc3339_utc(&row.planned_time_utc);
-								let out_ts = dt_utc
-									.map(|dt| colors.time(format_dt(dt, &out_tz)))
-									.unwrap_or_else(|| "-".to_string());
-								let utc_ts = dt_utc
-									.map(|dt| format_dt(dt, &utc_tz))
-									.unwrap_or_else(|| "-".to_string());
-
-								let status = status_tag(&colors, &row.status);
-								println!(
-									"#{idx1} slot {}: {} (UTC {}){}",
-									colors.slot(row.slot.to_string()),
-									out_ts,
-									colors.dim(utc_ts),
-									status,
-										idx1 = idx + 1
-									);
-								}
-								println!("{}={}", i18n.pick("Total", "合計"), schedule_rows.len());
-
-								if live_update && next_preview_printed {
-									println!();
-									let next_start_slot = (epoch_idx + 1) * epoch_size;
-									print_next_committee_for_author(
-										&i18n,
-										&colors,
-										&api,
-										&author_bytes,
-										latest_slot,
-										ts_ms,
-										slot_dur_ms,
-										next_start_slot,
-										&out_tz,
-										&utc_tz,
-									);
-								}
-							}
-						}
-					}
+		if let Some(ref metrics) = metrics {
+			let (counts, lags) = match conn.as_ref() {
+				Some(c) => (db_epoch_status_counts(c, epoch_idx)?, db_finality_lags(c, epoch_idx)?),
+				None => (Vec::new(), Vec::new()),
+			};
+			if finalized_number != finalized_advance.0 {
+				finalized_advance = (finalized_number, std::time::Instant::now());
+			}
+			let now_ms = chrono::Utc::now().timestamp_millis();
+			let mut snapshot = Metrics {
+				epoch: epoch_idx,
+				authority_set_len: auths.len() as u64,
+				best_block: head.best_number,
+				finalized_block: finalized_number,
+				finalized_head_age_secs: finalized_advance.1.elapsed().as_secs_f64(),
+				authors: Default::default(),
+			};
+			for ((key, view), registration) in keys.iter().zip(&views).zip(&registrations) {
+				let next_slot_seconds = view
+					.my_slots
+					.iter()
+					.find(|s| **s > latest_slot)
+					.map(|s| (planned_ts_ms(*s, latest_slot, ts_ms, slot_dur_ms) - now_ms).max(0) as f64 / 1000.0);
+				let blocks_by_status = counts
+					.iter()
+					.filter(|(author, _, _)| *author == key.author_hex)
+					.map(|(_, status, n)| (status.clone(), *n))
+					.collect();
+				let mut author_lags: Vec<i64> =
+					lags.iter().filter(|(author, _)| *author == key.author_hex).map(|(_, ms)| *ms).collect();
+				author_lags.sort_unstable();
+				let finality_lag_secs = [("0.5", 0.5), ("0.95", 0.95), ("1", 1.0)]
+					.into_iter()
+					.filter_map(|(label, q)| quantile(&author_lags, q).map(|ms| (label, ms as f64 / 1000.0)))
+					.collect();
+				snapshot.authors.insert(
+					key.author_hex.clone(),
+					AuthorMetrics {
+						in_committee: view.present == Some(true),
+						scheduled_slots: view.my_slots.len() as u64,
+						blocks_by_status,
+						next_slot_seconds,
+						registration_valid: registration.map(|(_, valid)| valid),
+						stake_lovelace: registration.map(|(lovelace, _)| lovelace),
+						finality_lag_secs,
+					},
+				);
+			}
+			if let Ok(mut m) = metrics.lock() {
+				*m = snapshot;
+			}
+		}
 
 				if !common.watch {
 					break;
@@ -2388,35 +2067,151 @@ fn run(common: CommonArgs) -> anyhow::Result<()> {
 				);
 			}
 
-			// Poll the chain once per slot (approx 1s) so mint/finality updates are not delayed.
 			if is_tty {
 				let cur = latest_slot.saturating_sub(start_slot);
 				let denom = epoch_size.max(1);
 				let pct = ((cur.saturating_mul(100)) / denom).min(100) as u8;
-				if waiting_progress_tick % 5 == 0 {
-					print_progress(
-						true,
+				print_progress(
+					true,
+					&colors,
+					i18n.pick("progress", "進捗"),
+					pct,
+					latest_slot,
+					epoch_end_slot,
+				);
+			}
+
+			// Block until the node announces a new best or finalized head; nothing is polled in between
+			// except reminders falling due before the next head.
+			let Some(ref rx) = heads else { break };
+			let first = loop {
+				let wait = reminders
+					.as_ref()
+					.and_then(|r| r.next_due_in(&reminder_slots(&views), chrono::Utc::now()));
+				let received = match wait {
+					Some(d) => rx.recv_timeout(d),
+					None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+				};
+				match (received, reminders.as_mut()) {
+					(Err(RecvTimeoutError::Timeout), Some(r)) => fire_reminders(
+						r,
+						&keys,
+						&views,
+						common.remind_command.as_deref(),
+						notifier.as_ref(),
+						epoch_size,
+						&out_tz,
+						&i18n,
 						&colors,
-						i18n.pick("progress", "進捗"),
-						pct,
-						latest_slot,
-						epoch_end_slot,
-					);
+					),
+					(received, _) => break received.ok(),
+				}
+			};
+			let Some(first) = first else {
+				let e = anyhow!("head subscription closed by the node");
+				let rx;
+				(api, rx, finalized_number, head) = reconnect(&common.ws, &mut watcher, &colors, &i18n, e);
+				heads = Some(rx);
+				continue;
+			};
+			let mut new_best = None;
+			let mut closed = None;
+			for event in std::iter::once(first).chain(rx.try_iter()) {
+				match event {
+					HeadEvent::Best(hdr) => new_best = Some(hdr),
+					HeadEvent::Finalized(hdr) => finalized_number = finalized_number.max(hdr.number.into()),
+					HeadEvent::Closed(reason) => closed = Some(reason),
+				}
+			}
+			// Either subscription ending leaves the stream half dead; rebuild both.
+			if let Some(reason) = closed {
+				let rx;
+				(api, rx, finalized_number, head) =
+					reconnect(&common.ws, &mut watcher, &colors, &i18n, anyhow!(reason));
+				heads = Some(rx);
+				continue;
+			}
+			// Authorities and Timestamp.Now are only re-read when the best head actually moved.
+			best_head_moved = new_best.is_some();
+			match new_best {
+				Some(hdr) => match watcher.observe_header(&api, &hdr) {
+					Ok(h) => head = h,
+					Err(e) => {
+						let rx;
+						(api, rx, finalized_number, head) = reconnect(&common.ws, &mut watcher, &colors, &i18n, e);
+						heads = Some(rx);
+					}
+				},
+				None => {
+					head.auths_changed = false;
+					head.epoch_switched = false;
 				}
 			}
-
-			waiting_progress_tick = waiting_progress_tick.wrapping_add(1);
-			std::thread::sleep(Duration::from_secs(1));
-			continue;
 		}
 
 	Ok(())
 }
 
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Watch mode: rebuild the API and head subscription after an RPC failure, retrying with exponential
+/// backoff. The watcher keeps its cursors, so the next scans backfill blocks produced while disconnected.
+fn reconnect(
+	ws: &str,
+	watcher: &mut Watcher,
+	colors: &Colors,
+	i18n: &I18n,
+	err: anyhow::Error,
+) -> (NodeApi, Receiver<HeadEvent>, u64, HeadState) {
+	println!();
+	eprintln!(
+		"{}: {}",
+		colors.error(i18n.pick("RPC connection lost", "RPC接続が切断されました")),
+		colors.dim(err.to_string())
+	);
+	let mut delay = Duration::from_secs(1);
+	loop {
+		eprintln!(
+			"{}",
+			colors.dim(format!(
+				"{} ({}s)",
+				i18n.pick("reconnecting...", "再接続中..."),
+				delay.as_secs()
+			))
+		);
+		std::thread::sleep(delay);
+		let mut attempt = || -> anyhow::Result<(NodeApi, Receiver<HeadEvent>, u64, HeadState)> {
+			let api = connect(ws)?;
+			let heads = api.subscribe_heads()?;
+			let finalized_number = api.finalized_number()?;
+			let head = watcher.observe_head(&api)?;
+			Ok((api, heads, finalized_number, head))
+		};
+		match attempt() {
+			Ok(resumed) => {
+				watcher.catch_up();
+				eprintln!("{}", colors.ok(i18n.pick("reconnected", "再接続しました")));
+				return resumed;
+			}
+			Err(e) => {
+				eprintln!(
+					"{}: {}",
+					colors.dim(i18n.pick("reconnect failed", "再接続に失敗しました")),
+					colors.dim(e.to_string())
+				);
+				delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+			}
+		}
+	}
+}
+
 fn main() -> anyhow::Result<()> {
 	let cli = Cli::parse();
 	match cli.command {
 		Command::Block(common) => run(common),
 		Command::Log(args) => run_block(args),
+		Command::Backfill(args) => run_backfill(args),
+		Command::Stats(args) => run_stats(args),
+		Command::Committee(args) => run_committee(args),
 	}
 }
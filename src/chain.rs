@@ -0,0 +1,480 @@
+use anyhow::anyhow;
+use scale_value::{Composite, Primitive, Value as ScaleValue, ValueDef};
+use sp_core::H256;
+use sp_runtime::generic::DigestItem;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use substrate_api_client::{
+	ac_node_api::{storage::GetStorageTypes, DecodeAsType},
+	ac_primitives::{config::Config, sr25519, DefaultRuntimeConfig, RpcParams},
+	rpc::{HandleSubscription, Request, Subscribe, TungsteniteRpcClient},
+	Api, GetChainInfo, GetStorage, SubscribeChain,
+};
+
+use crate::render::{Colors, I18n};
+
+pub mod scripted;
+
+/// Node API handle used throughout mblog (WS RPC via tungstenite).
+pub type NodeApi = Api<DefaultRuntimeConfig, TungsteniteRpcClient>;
+/// Block header type of the default runtime config.
+pub type Header = <DefaultRuntimeConfig as Config>::Header;
+/// One `CommitteeInfo` entry: the member's sidechain (cross-chain) public key and its session keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitteeMember {
+	/// Usually a compressed secp256k1 key (33 bytes).
+	pub sidechain: Vec<u8>,
+	pub aura: [u8; 32],
+	pub grandpa: [u8; 32],
+}
+
+/// Committee schedule as decoded from `CommitteeInfo`: one member per slot position.
+pub type CommitteeSchedule = Vec<CommitteeMember>;
+
+/// Head notification from `chain_subscribeNewHeads` / `chain_subscribeFinalizedHeads`.
+pub enum HeadEvent {
+	Best(Header),
+	Finalized(Header),
+	/// One of the subscriptions ended or failed; the other may still deliver heads, so the receiver
+	/// must treat the whole stream as lost.
+	Closed(String),
+}
+
+/// Read access to the chain state the watcher needs.
+///
+/// `NodeApi` is the live backend; [`scripted::ScriptedChain`] is an in-memory backend for driving
+/// the watch logic without a running midnight-node.
+pub trait ChainSource {
+	/// Hash of the current best block.
+	fn best_hash(&self) -> anyhow::Result<Option<H256>>;
+	/// Hash of the current finalized block.
+	fn finalized_hash(&self) -> anyhow::Result<Option<H256>>;
+	/// Canonical block hash at `number`.
+	fn block_hash(&self, number: u64) -> anyhow::Result<Option<H256>>;
+	fn header(&self, hash: H256) -> anyhow::Result<Option<Header>>;
+	/// `Aura.Authorities` at `at` (best block when `None`).
+	fn authorities(&self, at: Option<H256>) -> anyhow::Result<Vec<sr25519::Public>>;
+	/// `Timestamp.Now` (ms) at `at` (best block when `None`).
+	fn timestamp_ms(&self, at: Option<H256>) -> anyhow::Result<Option<u64>>;
+	/// `Aura.SlotDuration` constant (ms).
+	fn slot_duration_ms(&self) -> anyhow::Result<u64>;
+	/// `SessionCommitteeManagement.CurrentCommittee`, if the runtime exposes it.
+	fn current_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>>;
+	/// `SessionCommitteeManagement.NextCommittee`, if the runtime exposes it.
+	fn next_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>>;
+	/// Slots per (sidechain) epoch, if the runtime exposes it (`Sidechain.SlotsPerEpoch`).
+	fn epoch_size(&self) -> anyhow::Result<Option<u64>>;
+	/// Current epoch number according to the runtime (`Sidechain.EpochNumber`, else the
+	/// `SessionCommitteeManagement.CurrentCommittee` epoch).
+	fn current_epoch(&self) -> anyhow::Result<Option<u64>>;
+	/// Stream of new best and finalized heads. The receiver disconnects when the subscriptions end.
+	fn subscribe_heads(&self) -> anyhow::Result<Receiver<HeadEvent>>;
+
+	fn best_number(&self) -> anyhow::Result<u64> {
+		let Some(hash) = self.best_hash()? else {
+			return Ok(0);
+		};
+		Ok(self.header(hash)?.map(|hdr| hdr.number.into()).unwrap_or(0))
+	}
+
+	fn finalized_number(&self) -> anyhow::Result<u64> {
+		let Some(hash) = self.finalized_hash()? else {
+			return Ok(0);
+		};
+		Ok(self.header(hash)?.map(|hdr| hdr.number.into()).unwrap_or(0))
+	}
+
+	fn header_by_number(&self, number: u64) -> anyhow::Result<Option<(H256, Header)>> {
+		let Some(hash) = self.block_hash(number)? else {
+			return Ok(None);
+		};
+		Ok(self.header(hash)?.map(|hdr| (hash, hdr)))
+	}
+}
+
+impl ChainSource for NodeApi {
+	fn best_hash(&self) -> anyhow::Result<Option<H256>> {
+		self.get_block_hash(None).map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn finalized_hash(&self) -> anyhow::Result<Option<H256>> {
+		self.get_finalized_head().map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+		let bn_u32: u32 = number
+			.try_into()
+			.map_err(|_| anyhow!("block number {number} does not fit u32"))?;
+		self.get_block_hash(Some(bn_u32)).map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn header(&self, hash: H256) -> anyhow::Result<Option<Header>> {
+		self.get_header(Some(hash)).map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn authorities(&self, at: Option<H256>) -> anyhow::Result<Vec<sr25519::Public>> {
+		let res: Option<Vec<sr25519::Public>> = self
+			.get_storage("Aura", "Authorities", at)
+			.map_err(|e| anyhow!("{e:?}"))?;
+		Ok(res.unwrap_or_default())
+	}
+
+	fn timestamp_ms(&self, at: Option<H256>) -> anyhow::Result<Option<u64>> {
+		self.get_storage("Timestamp", "Now", at).map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn slot_duration_ms(&self) -> anyhow::Result<u64> {
+		self.get_constant("Aura", "SlotDuration").map_err(|e| anyhow!("{e:?}"))
+	}
+
+	fn current_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>> {
+		fetch_committee_info(self, "SessionCommitteeManagement", "CurrentCommittee")
+	}
+
+	fn next_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>> {
+		fetch_committee_info(self, "SessionCommitteeManagement", "NextCommittee")
+	}
+
+	fn epoch_size(&self) -> anyhow::Result<Option<u64>> {
+		fetch_plain_u64(self, "Sidechain", "SlotsPerEpoch")
+	}
+
+	fn current_epoch(&self) -> anyhow::Result<Option<u64>> {
+		if let Some(epoch) = fetch_plain_u64(self, "Sidechain", "EpochNumber")? {
+			return Ok(Some(epoch));
+		}
+		Ok(self.current_committee()?.map(|(epoch, _)| epoch))
+	}
+
+	fn subscribe_heads(&self) -> anyhow::Result<Receiver<HeadEvent>> {
+		let (tx, rx) = channel();
+		let best = self
+			.client()
+			.subscribe::<Header>("chain_subscribeNewHeads", RpcParams::new(), "chain_unsubscribeNewHeads")
+			.map_err(|e| anyhow!("chain_subscribeNewHeads failed: {e:?}"))?;
+		forward_heads(best, tx.clone(), "chain_subscribeNewHeads", HeadEvent::Best);
+		let finalized = self
+			.subscribe_finalized_heads()
+			.map_err(|e| anyhow!("chain_subscribeFinalizedHeads failed: {e:?}"))?;
+		forward_heads(finalized, tx, "chain_subscribeFinalizedHeads", HeadEvent::Finalized);
+		Ok(rx)
+	}
+}
+
+/// Pump a blocking subscription into the merged head channel until either side goes away. When the
+/// subscription ends or fails, a [`HeadEvent::Closed`] is sent so the consumer reconnects even though the
+/// other subscription still shares the channel.
+fn forward_heads<S>(mut sub: S, tx: Sender<HeadEvent>, name: &'static str, wrap: fn(Header) -> HeadEvent)
+where
+	S: HandleSubscription<Header> + Send + 'static,
+{
+	std::thread::spawn(move || {
+		let reason = loop {
+			match sub.next() {
+				Some(Ok(header)) => {
+					if tx.send(wrap(header)).is_err() {
+						return;
+					}
+				}
+				Some(Err(e)) => break format!("{name} failed: {e:?}"),
+				None => break format!("{name} closed by the node"),
+			}
+		};
+		let _ = tx.send(HeadEvent::Closed(reason));
+	});
+}
+
+pub fn connect(ws: &str) -> anyhow::Result<NodeApi> {
+	let client = TungsteniteRpcClient::new(ws, 3).map_err(|e| anyhow!("rpc client init failed: {e:?}"))?;
+	Api::new(client).map_err(|e| anyhow!("api init failed: {e:?}"))
+}
+
+pub fn aura_slot_from_header(header: &Header) -> Option<u64> {
+	for log in &header.digest.logs {
+		if let DigestItem::PreRuntime(engine_id, data) = log {
+			if engine_id != b"aura" {
+				continue;
+			}
+			let raw: [u8; 8] = data.get(0..8)?.try_into().ok()?;
+			return Some(u64::from_le_bytes(raw));
+		}
+	}
+	None
+}
+
+pub fn authorities_at<C: ChainSource>(
+	src: &C,
+	at_hash: H256,
+) -> anyhow::Result<Vec<sr25519::Public>> {
+	src.authorities(Some(at_hash))
+}
+
+pub fn fetch_authorities<C: ChainSource>(src: &C) -> anyhow::Result<Vec<sr25519::Public>> {
+	src.authorities(None)
+}
+
+pub fn block_time_utc<C: ChainSource>(src: &C, hash: H256) -> String {
+	block_onchain_time_utc(src, hash).unwrap_or_else(|| chrono::Utc::now().to_rfc3339())
+}
+
+/// `Timestamp.Now` of the block, without the local-clock fallback of [`block_time_utc`].
+pub fn block_onchain_time_utc<C: ChainSource>(src: &C, hash: H256) -> Option<String> {
+	let ts_ms: Option<u64> = src.timestamp_ms(Some(hash)).ok().flatten();
+	ts_ms.and_then(|ms| chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ms as i64)).map(|t| t.to_rfc3339())
+}
+
+pub fn author_has_aura_key(
+	api: &NodeApi,
+	public_key_hex: &str,
+) -> anyhow::Result<bool> {
+	let mut params = substrate_api_client::ac_primitives::RpcParams::new();
+	params
+		.insert(public_key_hex)
+		.map_err(|e| anyhow!("failed to build RPC params: {e}"))?;
+	params
+		.insert("aura")
+		.map_err(|e| anyhow!("failed to build RPC params: {e}"))?;
+
+	api.client()
+		.request("author_hasKey", params)
+		.map_err(|e| anyhow!("author_hasKey RPC failed: {e:?}"))
+}
+
+pub fn extract_plain_type_id(ty_dbg: &str) -> Option<u32> {
+	let pat = "Plain(UntrackedSymbol { id: ";
+	let rest = ty_dbg.strip_prefix(pat)?;
+	let n: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+	n.parse::<u32>().ok()
+}
+
+pub fn value_as_u64(v: &ScaleValue<()>) -> Option<u64> {
+	match &v.value {
+		ValueDef::Primitive(Primitive::U128(n)) => (*n).try_into().ok(),
+		_ => None,
+	}
+}
+
+pub fn value_as_unnamed(v: &ScaleValue<()>) -> Option<&[ScaleValue<()>]> {
+	match &v.value {
+		ValueDef::Composite(Composite::Unnamed(values)) => Some(values),
+		_ => None,
+	}
+}
+
+pub fn value_as_named(v: &ScaleValue<()>) -> Option<&[(String, ScaleValue<()>)]> {
+	match &v.value {
+		ValueDef::Composite(Composite::Named(values)) => Some(values),
+		_ => None,
+	}
+}
+
+pub fn value_as_bytes(v: &ScaleValue<()>) -> Option<Vec<u8>> {
+	let items = value_as_unnamed(v)?;
+	let mut out = Vec::with_capacity(items.len());
+	for it in items {
+		let b = value_as_u64(it)?;
+		let b: u8 = b.try_into().ok()?;
+		out.push(b);
+	}
+	Some(out)
+}
+
+pub fn value_as_wrapped_bytes(v: &ScaleValue<()>) -> Option<Vec<u8>> {
+	// Many values appear as Composite::Unnamed(len=1) -> Composite::Unnamed(len=N) -> [u8...]
+	let items = value_as_unnamed(v)?;
+	if items.len() != 1 {
+		return None;
+	}
+	value_as_bytes(&items[0])
+}
+
+pub fn value_as_wrapped_u64(v: &ScaleValue<()>) -> Option<u64> {
+	let items = value_as_unnamed(v)?;
+	if items.len() != 1 {
+		return None;
+	}
+	value_as_u64(&items[0])
+}
+
+/// Decode a plain storage item at the best block using the runtime metadata; `None` when the
+/// pallet/item does not exist or the value is unset.
+pub fn fetch_plain_value(
+	api: &NodeApi,
+	pallet_name: &str,
+	item_name: &str,
+) -> anyhow::Result<Option<ScaleValue<()>>> {
+	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
+		return Ok(None);
+	};
+	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
+		return Ok(None);
+	};
+	let ty_dbg = format!("{:?}", entry.ty);
+	let Some(type_id) = extract_plain_type_id(&ty_dbg) else {
+		return Ok(None);
+	};
+	let Ok(val) = entry.get_value(pallet_name) else {
+		return Ok(None);
+	};
+	let key = val.key();
+	let raw = match api.get_opaque_storage_by_key(key, None) {
+		Ok(Some(b)) => b,
+		Ok(None) => return Ok(None),
+		Err(e) => return Err(anyhow!("{e:?}")),
+	};
+	let v = ScaleValue::<()>::decode_as_type(&mut raw.as_slice(), type_id, api.metadata().types())
+		.map_err(|e| anyhow!("{e}"))?;
+	Ok(Some(v))
+}
+
+/// Plain storage integer, either bare or wrapped in a single-field newtype (e.g. `ScEpochNumber(u64)`).
+pub fn fetch_plain_u64(api: &NodeApi, pallet_name: &str, item_name: &str) -> anyhow::Result<Option<u64>> {
+	let Some(v) = fetch_plain_value(api, pallet_name, item_name)? else {
+		return Ok(None);
+	};
+	value_as_u64(&v)
+		.or_else(|| value_as_wrapped_u64(&v))
+		.map(Some)
+		.ok_or_else(|| anyhow!("{pallet_name}.{item_name} decode: expected an integer"))
+}
+
+pub fn fetch_committee_info(
+	api: &NodeApi,
+	pallet_name: &str,
+	item_name: &str,
+) -> anyhow::Result<Option<(u64, CommitteeSchedule)>> {
+	let Some(v) = fetch_plain_value(api, pallet_name, item_name)? else {
+		return Ok(None);
+	};
+
+	let named = value_as_named(&v).ok_or_else(|| anyhow!("CommitteeInfo decode: expected named composite"))?;
+	let epoch_v = named
+		.iter()
+		.find(|(k, _)| k == "epoch")
+		.ok_or_else(|| anyhow!("CommitteeInfo decode: missing epoch"))?
+		.1
+		.clone();
+	let committee_v = named
+		.iter()
+		.find(|(k, _)| k == "committee")
+		.ok_or_else(|| anyhow!("CommitteeInfo decode: missing committee"))?
+		.1
+		.clone();
+
+	let epoch = value_as_wrapped_u64(&epoch_v).ok_or_else(|| anyhow!("CommitteeInfo decode: epoch"))?;
+
+	let committee_outer = value_as_unnamed(&committee_v).ok_or_else(|| anyhow!("CommitteeInfo decode: committee outer"))?;
+	if committee_outer.len() != 1 {
+		return Err(anyhow!(
+			"CommitteeInfo decode: expected committee outer len=1, got {}",
+			committee_outer.len()
+		));
+	}
+	let schedule = value_as_unnamed(&committee_outer[0]).ok_or_else(|| anyhow!("CommitteeInfo decode: schedule"))?;
+
+	let mut out: CommitteeSchedule = Vec::with_capacity(schedule.len());
+	for entry in schedule {
+		let parts = value_as_unnamed(entry).ok_or_else(|| anyhow!("CommitteeInfo decode: entry parts"))?;
+		if parts.len() != 2 {
+			return Err(anyhow!("CommitteeInfo decode: expected entry len=2, got {}", parts.len()));
+		}
+		let sidechain =
+			value_as_wrapped_bytes(&parts[0]).ok_or_else(|| anyhow!("CommitteeInfo decode: sidechain bytes"))?;
+		let keys_named =
+			value_as_named(&parts[1]).ok_or_else(|| anyhow!("CommitteeInfo decode: keys named"))?;
+		let aura_v = keys_named
+			.iter()
+			.find(|(k, _)| k == "aura")
+			.ok_or_else(|| anyhow!("CommitteeInfo decode: missing aura"))?
+			.1
+			.clone();
+		let grandpa_v = keys_named
+			.iter()
+			.find(|(k, _)| k == "grandpa")
+			.ok_or_else(|| anyhow!("CommitteeInfo decode: missing grandpa"))?
+			.1
+			.clone();
+		let aura_bytes =
+			value_as_wrapped_bytes(&aura_v).ok_or_else(|| anyhow!("CommitteeInfo decode: aura bytes"))?;
+		let grandpa_bytes = value_as_wrapped_bytes(&grandpa_v)
+			.ok_or_else(|| anyhow!("CommitteeInfo decode: grandpa bytes"))?;
+		let aura_len = aura_bytes.len();
+		let aura_arr: [u8; 32] = aura_bytes
+			.try_into()
+			.map_err(|_| anyhow!("CommitteeInfo decode: aura len={aura_len}"))?;
+		let grandpa_len = grandpa_bytes.len();
+		let grandpa_arr: [u8; 32] = grandpa_bytes
+			.try_into()
+			.map_err(|_| anyhow!("CommitteeInfo decode: grandpa len={grandpa_len}"))?;
+		out.push(CommitteeMember { sidechain, aura: aura_arr, grandpa: grandpa_arr });
+	}
+
+	Ok(Some((epoch, out)))
+}
+
+/// Slots per epoch when neither the runtime nor `--epoch-size` gives one.
+pub const DEFAULT_EPOCH_SIZE: u64 = 1200;
+
+/// Slots per epoch: the runtime's value when it exposes one, else `--epoch-size` (or the default).
+/// A flag that disagrees with the runtime is reported and ignored.
+pub fn resolve_epoch_size<C: ChainSource>(src: &C, flag: Option<u32>, i18n: &I18n, colors: &Colors) -> u64 {
+	let flag = flag.map(u64::from);
+	let runtime = match src.epoch_size() {
+		Ok(v) => v.filter(|n| *n > 0),
+		Err(e) => {
+			eprintln!(
+				"{}: {}",
+				colors.dim(i18n.pick("epoch length read failed", "エポック長の取得に失敗しました")),
+				colors.dim(e.to_string())
+			);
+			None
+		}
+	};
+	match (runtime, flag) {
+		(Some(rt), Some(f)) if rt != f => {
+			eprintln!(
+				"{}",
+				colors.error(format!(
+					"{}: --epoch-size={f}, runtime={rt} ({})",
+					i18n.pick("WARNING: epoch size mismatch", "警告: エポック長が一致しません"),
+					i18n.pick("using the runtime value", "ランタイムの値を使用します")
+				))
+			);
+			rt
+		}
+		(Some(rt), _) => rt,
+		(None, Some(f)) => f,
+		(None, None) => {
+			eprintln!(
+				"{}",
+				colors.error(format!(
+					"{} ({DEFAULT_EPOCH_SIZE})",
+					i18n.pick(
+						"WARNING: runtime does not expose the epoch length; using the default --epoch-size",
+						"警告: ランタイムからエポック長を取得できません。既定の --epoch-size を使用します"
+					)
+				))
+			);
+			DEFAULT_EPOCH_SIZE
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::render::{ColorMode, Lang};
+
+	#[test]
+	fn epoch_size_prefers_the_runtime() {
+		let (i18n, colors) = (I18n::new(Lang::En), Colors::new(ColorMode::Never));
+		let mut src = scripted::ScriptedChain::new(6000, Vec::new());
+		src.set_epoch_size(Some(300));
+		assert_eq!(resolve_epoch_size(&src, None, &i18n, &colors), 300);
+		assert_eq!(resolve_epoch_size(&src, Some(1200), &i18n, &colors), 300);
+		src.set_epoch_size(Some(0));
+		assert_eq!(resolve_epoch_size(&src, Some(60), &i18n, &colors), 60);
+		src.set_epoch_size(None);
+		assert_eq!(resolve_epoch_size(&src, Some(60), &i18n, &colors), 60);
+		assert_eq!(resolve_epoch_size(&src, None, &i18n, &colors), DEFAULT_EPOCH_SIZE);
+	}
+}
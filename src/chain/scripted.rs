@@ -0,0 +1,181 @@
+use sp_core::H256;
+use sp_runtime::generic::{Digest, DigestItem};
+use sp_runtime::traits::Header as _;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use substrate_api_client::ac_primitives::sr25519;
+
+use super::{aura_slot_from_header, ChainSource, CommitteeSchedule, HeadEvent, Header};
+
+struct ScriptedBlock {
+	header: Header,
+	timestamp_ms: u64,
+	authorities: Vec<sr25519::Public>,
+}
+
+/// In-memory chain that a test drives block by block.
+///
+/// Blocks are appended to the canonical chain with [`ScriptedChain::push_block`]; `Aura.Authorities`
+/// and `Timestamp.Now` are recorded per block so historical reads (`authorities_at(parent_hash)`)
+/// behave like an archive node.
+pub struct ScriptedChain {
+	blocks: HashMap<H256, ScriptedBlock>,
+	canonical: Vec<H256>,
+	finalized: u64,
+	slot_duration_ms: u64,
+	authorities: Vec<sr25519::Public>,
+	current_committee: Option<(u64, CommitteeSchedule)>,
+	next_committee: Option<(u64, CommitteeSchedule)>,
+	epoch_size: Option<u64>,
+	subscribers: RefCell<Vec<Sender<HeadEvent>>>,
+}
+
+impl ScriptedChain {
+	/// Chain with a genesis block (number 0, no Aura digest) and the given authority set.
+	pub fn new(slot_duration_ms: u64, authorities: Vec<sr25519::Public>) -> Self {
+		let genesis = Header::new(0, H256::zero(), H256::zero(), H256::zero(), Digest::default());
+		let hash = genesis.hash();
+		let mut blocks = HashMap::new();
+		blocks.insert(
+			hash,
+			ScriptedBlock { header: genesis, timestamp_ms: 0, authorities: authorities.clone() },
+		);
+		Self {
+			blocks,
+			canonical: vec![hash],
+			finalized: 0,
+			slot_duration_ms,
+			authorities,
+			current_committee: None,
+			next_committee: None,
+			epoch_size: None,
+			subscribers: RefCell::new(Vec::new()),
+		}
+	}
+
+	/// Append a block authored in `slot` on top of the best block; `Timestamp.Now` is `slot * SlotDuration`.
+	pub fn push_block(&mut self, slot: u64) -> H256 {
+		let parent_hash = *self.canonical.last().expect("genesis is always present");
+		let number = self.canonical.len() as u32;
+		let digest = Digest {
+			logs: vec![DigestItem::PreRuntime(*b"aura", slot.to_le_bytes().to_vec())],
+		};
+		let header = Header::new(number, H256::zero(), H256::zero(), parent_hash, digest);
+		let hash = header.hash();
+		self.notify(|| HeadEvent::Best(header.clone()));
+		self.blocks.insert(
+			hash,
+			ScriptedBlock {
+				header,
+				timestamp_ms: slot * self.slot_duration_ms,
+				authorities: self.authorities.clone(),
+			},
+		);
+		self.canonical.push(hash);
+		hash
+	}
+
+	/// Reorg: drop canonical blocks above `number` (still readable by hash); blocks pushed afterwards
+	/// form the new fork.
+	pub fn rewind(&mut self, number: u64) {
+		let keep = (number.max(self.finalized) + 1) as usize;
+		self.canonical.truncate(keep);
+	}
+
+	/// Mark every canonical block up to `number` as finalized.
+	pub fn finalize(&mut self, number: u64) {
+		let best = self.canonical.len() as u64 - 1;
+		self.finalized = number.min(best);
+		let header = self.blocks[&self.canonical[self.finalized as usize]].header.clone();
+		self.notify(|| HeadEvent::Finalized(header.clone()));
+	}
+
+	/// Authority set stored by blocks pushed from now on.
+	pub fn set_authorities(&mut self, authorities: Vec<sr25519::Public>) {
+		self.authorities = authorities;
+	}
+
+	pub fn set_current_committee(&mut self, committee: Option<(u64, CommitteeSchedule)>) {
+		self.current_committee = committee;
+	}
+
+	pub fn set_next_committee(&mut self, committee: Option<(u64, CommitteeSchedule)>) {
+		self.next_committee = committee;
+	}
+
+	/// Slots per epoch reported as the runtime's; `None` models a runtime without `Sidechain.SlotsPerEpoch`.
+	pub fn set_epoch_size(&mut self, epoch_size: Option<u64>) {
+		self.epoch_size = epoch_size;
+	}
+
+	/// Drop every head subscription, as a closed RPC connection would.
+	pub fn disconnect(&mut self) {
+		self.subscribers.borrow_mut().clear();
+	}
+
+	fn notify(&self, event: impl Fn() -> HeadEvent) {
+		self.subscribers.borrow_mut().retain(|tx| tx.send(event()).is_ok());
+	}
+}
+
+impl ChainSource for ScriptedChain {
+	fn best_hash(&self) -> anyhow::Result<Option<H256>> {
+		Ok(self.canonical.last().copied())
+	}
+
+	fn finalized_hash(&self) -> anyhow::Result<Option<H256>> {
+		Ok(self.canonical.get(self.finalized as usize).copied())
+	}
+
+	fn block_hash(&self, number: u64) -> anyhow::Result<Option<H256>> {
+		Ok(self.canonical.get(number as usize).copied())
+	}
+
+	fn header(&self, hash: H256) -> anyhow::Result<Option<Header>> {
+		Ok(self.blocks.get(&hash).map(|b| b.header.clone()))
+	}
+
+	fn authorities(&self, at: Option<H256>) -> anyhow::Result<Vec<sr25519::Public>> {
+		let at = at.or_else(|| self.canonical.last().copied());
+		Ok(at
+			.and_then(|h| self.blocks.get(&h))
+			.map(|b| b.authorities.clone())
+			.unwrap_or_default())
+	}
+
+	fn timestamp_ms(&self, at: Option<H256>) -> anyhow::Result<Option<u64>> {
+		let at = at.or_else(|| self.canonical.last().copied());
+		Ok(at.and_then(|h| self.blocks.get(&h)).map(|b| b.timestamp_ms))
+	}
+
+	fn slot_duration_ms(&self) -> anyhow::Result<u64> {
+		Ok(self.slot_duration_ms)
+	}
+
+	fn current_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>> {
+		Ok(self.current_committee.clone())
+	}
+
+	fn next_committee(&self) -> anyhow::Result<Option<(u64, CommitteeSchedule)>> {
+		Ok(self.next_committee.clone())
+	}
+
+	fn epoch_size(&self) -> anyhow::Result<Option<u64>> {
+		Ok(self.epoch_size)
+	}
+
+	fn current_epoch(&self) -> anyhow::Result<Option<u64>> {
+		let Some(epoch_size) = self.epoch_size else {
+			return Ok(None);
+		};
+		let best = self.canonical.last().and_then(|h| self.blocks.get(h));
+		Ok(best.and_then(|b| aura_slot_from_header(&b.header)).map(|slot| slot / epoch_size))
+	}
+
+	fn subscribe_heads(&self) -> anyhow::Result<Receiver<HeadEvent>> {
+		let (tx, rx) = channel();
+		self.subscribers.borrow_mut().push(tx);
+		Ok(rx)
+	}
+}
@@ -0,0 +1,424 @@
+use anyhow::anyhow;
+use scale_value::{Composite, Primitive, Value as ScaleValue, ValueDef, Variant};
+use sp_core::crypto::{AccountId32, KeyTypeId};
+use substrate_api_client::{
+	ac_node_api::{storage::GetStorageTypes, DecodeAsType},
+	GetStorage,
+};
+
+use crate::chain::{
+	extract_plain_type_id, value_as_named, value_as_unnamed, value_as_wrapped_bytes,
+	value_as_wrapped_u64, NodeApi,
+};
+use crate::render::hex0x;
+
+fn format_meta_check(res: Result<(), anyhow::Error>) -> String {
+	match res {
+		Ok(()) => "ok".to_string(),
+		Err(e) => format!("err: {e}"),
+	}
+}
+
+fn hex_bytes_abbrev(bytes: &[u8], max_hex_chars: usize) -> String {
+	let h = hex::encode(bytes);
+	if h.len() <= max_hex_chars {
+		return format!("0x{h}");
+	}
+	format!("0x{}...{}", &h[..max_hex_chars], &h[h.len().saturating_sub(32)..])
+}
+
+pub fn print_pallets(api: &NodeApi, filter: Option<&str>) {
+	let filter_lc = filter.map(|s| s.to_lowercase());
+	for p in api.metadata().pallets() {
+		let name = p.name();
+		if let Some(ref f) = filter_lc
+			&& !name.to_lowercase().contains(f) {
+				continue;
+			}
+		let storage_len = p.storage().len();
+		println!("{name} (index={}, storage={storage_len})", p.index());
+	}
+}
+
+pub fn debug_read_plain_storage(
+	api: &NodeApi,
+	pallet_name: &str,
+	item_name: &str,
+) {
+	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
+		eprintln!("debug-storage: pallet not found: {pallet_name}");
+		return;
+	};
+	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
+		eprintln!("debug-storage: storage item not found: {pallet_name}.{item_name}");
+		return;
+	};
+
+	let Ok(val) = entry.get_value(pallet_name) else {
+		eprintln!(
+			"debug-storage: {pallet_name}.{item_name} is not a plain value storage (ty={:?})",
+			entry.ty
+		);
+		return;
+	};
+
+	let ty_dbg = format!("{:?}", entry.ty);
+	if let Some(type_id) = extract_plain_type_id(&ty_dbg) {
+		let type_path = api
+			.metadata()
+			.resolve_type(type_id)
+			.map(|t| t.path.to_string())
+			.filter(|s| !s.is_empty())
+			.unwrap_or_else(|| "<unknown>".to_string());
+		println!("debug-storage: type=Plain(id={type_id}, path={type_path})");
+	}
+
+	let key = val.key();
+	println!("debug-storage: key=0x{}", hex::encode(&key.0));
+	match api.get_opaque_storage_by_key(key, None) {
+		Ok(Some(raw)) => {
+			println!(
+				"debug-storage: value_bytes={} {}",
+				raw.len(),
+				hex_bytes_abbrev(&raw, 256)
+			);
+		}
+		Ok(None) => println!("debug-storage: value=null"),
+		Err(e) => eprintln!("debug-storage: read error: {e:?}"),
+	}
+}
+
+fn summarize_primitive(p: &Primitive) -> String {
+	match p {
+		Primitive::Bool(b) => b.to_string(),
+		Primitive::Char(c) => c.to_string(),
+		Primitive::String(s) => {
+			if s.len() <= 120 {
+				format!("{s:?}")
+			} else {
+				format!("{:?}...", &s[..120])
+			}
+		}
+		Primitive::U128(n) => n.to_string(),
+		Primitive::I128(n) => n.to_string(),
+		Primitive::U256(b) | Primitive::I256(b) => format!("0x{}", hex::encode(b)),
+	}
+}
+
+fn debug_pretty_committee_info(v: &ScaleValue<()>, max_items: usize) -> Option<Vec<String>> {
+	// Expected shape:
+	// Composite::Named { epoch: (u64), committee: [slot -> (sidechain_pub_key(33), {aura(32), grandpa(32)})] }
+	let named = value_as_named(v)?;
+	let epoch_v = named.iter().find(|(k, _)| k == "epoch")?.1.clone();
+	let committee_v = named.iter().find(|(k, _)| k == "committee")?.1.clone();
+
+	let epoch = value_as_wrapped_u64(&epoch_v)?;
+	let committee_outer = value_as_unnamed(&committee_v)?;
+	if committee_outer.len() != 1 {
+		return None;
+	}
+	let schedule = value_as_unnamed(&committee_outer[0])?;
+
+	let mut out = Vec::new();
+	out.push(format!("CommitteeInfo(epoch={epoch}, slots={})", schedule.len()));
+
+	let mut shown = 0usize;
+	for (idx, entry) in schedule.iter().enumerate() {
+		if shown >= max_items {
+			break;
+		}
+		let parts = value_as_unnamed(entry)?;
+		if parts.len() != 2 {
+			continue;
+		}
+		let sidechain = value_as_wrapped_bytes(&parts[0])?;
+		let keys_named = value_as_named(&parts[1])?;
+		let aura_v = keys_named.iter().find(|(k, _)| k == "aura")?.1.clone();
+		let grandpa_v = keys_named.iter().find(|(k, _)| k == "grandpa")?.1.clone();
+		let aura = value_as_wrapped_bytes(&aura_v)?;
+		let grandpa = value_as_wrapped_bytes(&grandpa_v)?;
+
+		out.push(format!(
+			"  slot[{idx}]: sidechain={} aura={} grandpa={}",
+			hex0x(&sidechain),
+			hex0x(&aura),
+			hex0x(&grandpa)
+		));
+		shown += 1;
+	}
+
+	if schedule.len() > shown {
+		out.push(format!("  ... ({} more)", schedule.len().saturating_sub(shown)));
+	}
+	Some(out)
+}
+
+fn summarize_value_lines(
+	v: &ScaleValue<()>,
+	depth: usize,
+	indent: usize,
+	max_depth: usize,
+	max_items: usize,
+) -> Vec<String> {
+	let pad = " ".repeat(indent);
+	match &v.value {
+		ValueDef::Primitive(p) => vec![format!("{pad}{}", summarize_primitive(p))],
+		ValueDef::BitSequence(bits) => vec![format!("{pad}bits(len={})", bits.len())],
+		ValueDef::Variant(Variant { name, values }) => {
+			let mut out = vec![format!("{pad}Variant({name})")];
+			if depth >= max_depth {
+				out.push(format!("{pad}  ..."));
+				return out;
+			}
+			out.extend(summarize_composite_lines(
+				values,
+				depth + 1,
+				indent + 2,
+				max_depth,
+				max_items,
+			));
+			out
+		}
+		ValueDef::Composite(c) => summarize_composite_lines(c, depth, indent, max_depth, max_items),
+	}
+}
+
+fn summarize_composite_lines(
+	c: &Composite<()>,
+	depth: usize,
+	indent: usize,
+	max_depth: usize,
+	max_items: usize,
+) -> Vec<String> {
+	let pad = " ".repeat(indent);
+	match c {
+		Composite::Named(fields) => {
+			let mut out = vec![format!("{pad}Composite::Named(len={})", fields.len())];
+			if depth >= max_depth {
+				out.push(format!("{pad}  ..."));
+				return out;
+			}
+			for (idx, (k, v)) in fields.iter().enumerate() {
+				if idx >= max_items {
+					out.push(format!("{pad}  ... ({} more)", fields.len().saturating_sub(max_items)));
+					break;
+				}
+				let mut lines = summarize_value_lines(v, depth + 1, indent + 4, max_depth, max_items);
+				if let Some(first) = lines.first_mut() {
+					*first = format!("{pad}  {k}: {}", first.trim_start());
+				}
+				out.extend(lines);
+			}
+			out
+		}
+		Composite::Unnamed(values) => {
+			let mut out = vec![format!("{pad}Composite::Unnamed(len={})", values.len())];
+			if depth >= max_depth {
+				out.push(format!("{pad}  ..."));
+				return out;
+			}
+			for (idx, v) in values.iter().enumerate() {
+				if idx >= max_items {
+					out.push(format!("{pad}  ... ({} more)", values.len().saturating_sub(max_items)));
+					break;
+				}
+				let mut lines = summarize_value_lines(v, depth + 1, indent + 4, max_depth, max_items);
+				if let Some(first) = lines.first_mut() {
+					*first = format!("{pad}  [{idx}]: {}", first.trim_start());
+				}
+				out.extend(lines);
+			}
+			out
+		}
+	}
+}
+
+pub fn debug_decode_plain_storage(
+	api: &NodeApi,
+	pallet_name: &str,
+	item_name: &str,
+	max_depth: usize,
+	max_items: usize,
+) {
+	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
+		eprintln!("debug-decode-storage: pallet not found: {pallet_name}");
+		return;
+	};
+	let Some(entry) = pallet.storage().find(|e| e.name == item_name) else {
+		eprintln!("debug-decode-storage: storage item not found: {pallet_name}.{item_name}");
+		return;
+	};
+	let ty_dbg = format!("{:?}", entry.ty);
+	let Some(type_id) = extract_plain_type_id(&ty_dbg) else {
+		eprintln!("debug-decode-storage: storage is not Plain: {ty_dbg}");
+		return;
+	};
+	let type_path = api
+		.metadata()
+		.resolve_type(type_id)
+		.map(|t| t.path.to_string())
+		.filter(|s| !s.is_empty())
+		.unwrap_or_else(|| "<unknown>".to_string());
+
+	let Ok(val) = entry.get_value(pallet_name) else {
+		eprintln!("debug-decode-storage: {pallet_name}.{item_name} is not a plain value storage");
+		return;
+	};
+	let key = val.key();
+	let raw = match api.get_opaque_storage_by_key(key, None) {
+		Ok(Some(b)) => b,
+		Ok(None) => {
+			println!("debug-decode-storage: value=null");
+			return;
+		}
+		Err(e) => {
+			eprintln!("debug-decode-storage: read error: {e:?}");
+			return;
+		}
+	};
+
+	println!("debug-decode-storage: type=Plain(id={type_id}, path={type_path})");
+	match ScaleValue::<()>::decode_as_type(&mut raw.as_slice(), type_id, api.metadata().types()) {
+		Ok(v) => {
+			println!("debug-decode-storage: decoded (summary)");
+			if type_path == "pallet_session_validator_management::pallet::CommitteeInfo"
+				&& let Some(lines) = debug_pretty_committee_info(&v, max_items) {
+					for line in lines {
+						println!("{line}");
+					}
+					return;
+				}
+			for line in summarize_value_lines(&v, 0, 0, max_depth, max_items) {
+				println!("{line}");
+			}
+		}
+		Err(e) => {
+			eprintln!("debug-decode-storage: decode error: {e}");
+		}
+	}
+}
+
+pub fn debug_list_storage(
+	api: &NodeApi,
+	pallet_name: &str,
+	filter: Option<&str>,
+) {
+	let Some(pallet) = api.metadata().pallet_by_name(pallet_name) else {
+		eprintln!("debug-storage-list: pallet not found: {pallet_name}");
+		return;
+	};
+
+	let filter_lc = filter.map(|s| s.to_lowercase());
+	println!("{pallet_name} storage entries");
+	println!("---------------------");
+	for entry in pallet.storage() {
+		let name = entry.name.as_str();
+		if let Some(ref f) = filter_lc
+			&& !name.to_lowercase().contains(f) {
+				continue;
+			}
+		let ty_dbg = format!("{:?}", entry.ty);
+		if let Some(type_id) = extract_plain_type_id(&ty_dbg) {
+			let type_path = api
+				.metadata()
+				.resolve_type(type_id)
+				.map(|t| t.path.to_string())
+				.filter(|s| !s.is_empty())
+				.unwrap_or_else(|| "<unknown>".to_string());
+			println!("{name}: Plain(id={type_id}, path={type_path})");
+		} else {
+			println!("{name}: {ty_dbg}");
+		}
+	}
+}
+
+pub fn debug_session_metadata(
+	api: &NodeApi,
+	author_bytes: &[u8; 32],
+) -> Vec<(String, String)> {
+	let mut rows = Vec::new();
+
+	rows.push((
+		"meta Session pallet".to_string(),
+		if api.metadata().pallet_by_name("Session").is_some() {
+			"present".to_string()
+		} else {
+			"missing".to_string()
+		},
+	));
+
+	let check = |pallet: &'static str, item: &'static str| -> Result<(), anyhow::Error> {
+		api.metadata()
+			.storage_value_key(pallet, item)
+			.map(|_| ())
+			.map_err(|e| anyhow!("{e:?}"))
+	};
+	let check_map = |pallet: &'static str, item: &'static str| -> Result<(), anyhow::Error> {
+		api.metadata()
+			.storage_map_key_prefix(pallet, item)
+			.map(|_| ())
+			.map_err(|e| anyhow!("{e:?}"))
+	};
+	let check_double_prefix =
+		|pallet: &'static str, item: &'static str, first: KeyTypeId| -> Result<(), anyhow::Error> {
+			api.metadata()
+				.storage_double_map_key_prefix(pallet, item, first)
+				.map(|_| ())
+				.map_err(|e| anyhow!("{e:?}"))
+		};
+
+	rows.push((
+		"meta Session.Validators".to_string(),
+		format_meta_check(check("Session", "Validators")),
+	));
+	rows.push((
+		"meta Session.QueuedValidators".to_string(),
+		format_meta_check(check("Session", "QueuedValidators")),
+	));
+	rows.push((
+		"meta Session.QueuedKeys".to_string(),
+		format_meta_check(check("Session", "QueuedKeys")),
+	));
+	rows.push((
+		"meta Session.NextKeys(map)".to_string(),
+		format_meta_check(check_map("Session", "NextKeys")),
+	));
+	rows.push((
+		"meta Session.KeyOwner(dbl)".to_string(),
+		format_meta_check(check_double_prefix("Session", "KeyOwner", KeyTypeId(*b"aura"))),
+	));
+
+	// State checks (do not swallow errors)
+	match api.get_storage::<Vec<AccountId32>>("Session", "Validators", None) {
+		Ok(Some(vs)) => rows.push(("state Session.Validators".to_string(), format!("len={}", vs.len()))),
+		Ok(None) => rows.push(("state Session.Validators".to_string(), "null".to_string())),
+		Err(e) => rows.push(("state Session.Validators".to_string(), format!("err: {e:?}"))),
+	}
+	match api.get_storage::<Vec<AccountId32>>("Session", "QueuedValidators", None) {
+		Ok(Some(vs)) => rows.push((
+			"state Session.QueuedValidators".to_string(),
+			format!("len={}", vs.len()),
+		)),
+		Ok(None) => rows.push(("state Session.QueuedValidators".to_string(), "null".to_string())),
+		Err(e) => rows.push(("state Session.QueuedValidators".to_string(), format!("err: {e:?}"))),
+	}
+
+	let key_type = KeyTypeId(*b"aura");
+	let pubkey = author_bytes.to_vec();
+	match api.get_storage_double_map::<KeyTypeId, Vec<u8>, AccountId32>("Session", "KeyOwner", key_type, pubkey, None)
+	{
+		Ok(Some(a)) => rows.push(("state Session.KeyOwner(aura)".to_string(), hex_account_id32(&a))),
+		Ok(None) => rows.push(("state Session.KeyOwner(aura)".to_string(), "null".to_string())),
+		Err(e) => rows.push(("state Session.KeyOwner(aura)".to_string(), format!("err: {e:?}"))),
+	}
+
+	rows
+}
+
+fn account_id32_bytes(a: &AccountId32) -> &[u8] {
+	<AccountId32 as AsRef<[u8]>>::as_ref(a)
+}
+
+fn hex_account_id32(a: &AccountId32) -> String {
+	format!("0x{}", hex::encode(account_id32_bytes(a)))
+}
@@ -0,0 +1,122 @@
+use anyhow::anyhow;
+use std::path::Path;
+
+pub fn parse_pubkey_hex(s: &str) -> anyhow::Result<[u8; 32]> {
+	let hex_str = s.trim_start_matches("0x");
+	let bytes = hex::decode(hex_str)?;
+	let len = bytes.len();
+	let arr: [u8; 32] = bytes
+		.as_slice()
+		.try_into()
+		.map_err(|_| anyhow::anyhow!("expected 32-byte hex, got {} bytes", len))?;
+	Ok(arr)
+}
+
+pub fn detect_aura_pubkey_from_keystore(keystore_path: &Path) -> anyhow::Result<String> {
+	let mut found = detect_aura_pubkeys_from_keystore(keystore_path)?;
+	match found.len() {
+		1 => Ok(found.remove(0)),
+		_ => Err(anyhow!(
+			"multiple Aura keys found in keystore '{}': {:?}. Keep only one Aura key, or use a dedicated keystore path.",
+			keystore_path.display(),
+			found
+		)),
+	}
+}
+
+/// Every Aura public key in the keystore (sorted, deduplicated); errors when there is none.
+pub fn detect_aura_pubkeys_from_keystore(keystore_path: &Path) -> anyhow::Result<Vec<String>> {
+	let mut found: Vec<String> = Vec::new();
+
+	for entry in std::fs::read_dir(keystore_path).map_err(|e| {
+		anyhow!("failed to read --keystore-path '{}': {e}", keystore_path.display())
+	})? {
+		let entry = entry.map_err(|e| anyhow!("failed to read directory entry: {e}"))?;
+		let file_type = entry.file_type().map_err(|e| anyhow!("failed to stat entry: {e}"))?;
+		if !file_type.is_file() {
+			continue;
+		}
+		let name_os = entry.file_name();
+		let Some(name) = name_os.to_str() else {
+			continue;
+		};
+		let mut hex_name = name.trim().to_ascii_lowercase();
+		if let Some(rest) = hex_name.strip_prefix("0x") {
+			hex_name = rest.to_string();
+		}
+		// Substrate keystore filenames are typically: <4-byte key type><32-byte pubkey> as hex.
+		// For Aura, key type is "aura" => 0x61757261.
+		if hex_name.len() == 72 && hex_name.starts_with("61757261") {
+			let pub_hex = &hex_name[8..];
+			if pub_hex.chars().all(|c| c.is_ascii_hexdigit()) {
+				found.push(format!("0x{pub_hex}"));
+			}
+		}
+	}
+
+	found.sort();
+	found.dedup();
+
+	if found.is_empty() {
+		return Err(anyhow!(
+			"no Aura key found in keystore '{}': expected a file named like 61757261<pubkey32bytes> (hex)",
+			keystore_path.display()
+		));
+	}
+	Ok(found)
+}
+
+pub fn detect_sidechain_pubkey_from_keystore(keystore_path: &Path) -> anyhow::Result<String> {
+	let mut found: Vec<String> = Vec::new();
+
+	for entry in std::fs::read_dir(keystore_path).map_err(|e| {
+		anyhow!(
+			"failed to read --keystore-path '{}' for sidechain key detection: {e}",
+			keystore_path.display()
+		)
+	})? {
+		let entry = entry.map_err(|e| anyhow!("failed to read directory entry: {e}"))?;
+		let file_type = entry.file_type().map_err(|e| anyhow!("failed to stat entry: {e}"))?;
+		if !file_type.is_file() {
+			continue;
+		}
+		let name_os = entry.file_name();
+		let Some(name) = name_os.to_str() else {
+			continue;
+		};
+		let mut hex_name = name.trim().to_ascii_lowercase();
+		if let Some(rest) = hex_name.strip_prefix("0x") {
+			hex_name = rest.to_string();
+		}
+
+		// Expect: <4-byte key type><33-byte compressed pubkey> as hex.
+		// Many sidechain keys are compressed secp256k1 (33 bytes) starting with 02/03.
+		if hex_name.len() != 74 {
+			continue;
+		}
+		let pub_hex = &hex_name[8..];
+		if !pub_hex.chars().all(|c| c.is_ascii_hexdigit()) {
+			continue;
+		}
+		if !(pub_hex.starts_with("02") || pub_hex.starts_with("03")) {
+			continue;
+		}
+		found.push(format!("0x{pub_hex}"));
+	}
+
+	found.sort();
+	found.dedup();
+
+	match found.len() {
+		0 => Err(anyhow!(
+			"no sidechain public key found in keystore '{}': expected a file named like <keytype><33-byte pubkey> (hex, starts with 02/03)",
+			keystore_path.display()
+		)),
+		1 => Ok(found.remove(0)),
+		_ => Err(anyhow!(
+			"multiple sidechain public keys found in keystore '{}': {:?}. Keep only one sidechain key, or use a dedicated keystore path.",
+			keystore_path.display(),
+			found
+		)),
+	}
+}
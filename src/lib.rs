@@ -0,0 +1,16 @@
+//! Library half of `mblog`: Aura schedule computation, chain access, SQLite storage,
+//! sidechain registration checks and terminal rendering.
+
+pub mod backfill;
+pub mod chain;
+pub mod debug;
+pub mod keystore;
+pub mod metrics;
+pub mod notify;
+pub mod registration;
+pub mod remind;
+pub mod render;
+pub mod schedule;
+pub mod stats;
+pub mod store;
+pub mod watch;
@@ -0,0 +1,163 @@
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Statuses exported per author as `mblog_epoch_blocks{status=...}`.
+pub const EXPORTED_STATUSES: [&str; 4] = ["mint", "finality", "missed", "orphaned"];
+
+/// A scrape client that stalls must not hold up the (single) serving thread.
+const CLIENT_IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Snapshot of the watch loop exported on `--metrics-addr`.
+#[derive(Default, Clone)]
+pub struct Metrics {
+	pub epoch: u64,
+	pub authority_set_len: u64,
+	pub best_block: u64,
+	pub finalized_block: u64,
+	/// Seconds since the finalized head last advanced; grows during GRANDPA stalls.
+	pub finalized_head_age_secs: f64,
+	/// Keyed by Aura public key (0x hex).
+	pub authors: BTreeMap<String, AuthorMetrics>,
+}
+
+#[derive(Default, Clone)]
+pub struct AuthorMetrics {
+	pub in_committee: bool,
+	pub scheduled_slots: u64,
+	/// Current-epoch block count by status (`mint`, `finality`, `missed`, `orphaned`).
+	pub blocks_by_status: BTreeMap<String, u64>,
+	/// Seconds until the next scheduled slot of this epoch; `None` when no slot is left.
+	pub next_slot_seconds: Option<f64>,
+	pub registration_valid: Option<bool>,
+	pub stake_lovelace: Option<u128>,
+	/// Current-epoch time from first seeing our block to seeing it final: `(quantile label, seconds)`.
+	pub finality_lag_secs: Vec<(&'static str, f64)>,
+}
+
+pub type SharedMetrics = Arc<Mutex<Metrics>>;
+
+/// Prometheus text exposition format (version 0.0.4).
+pub fn render_metrics(m: &Metrics) -> String {
+	let mut out = String::new();
+	let mut gauge = |name: &str, help: &str, samples: Vec<(String, String)>| {
+		let _ = writeln!(out, "# HELP {name} {help}");
+		let _ = writeln!(out, "# TYPE {name} gauge");
+		for (labels, value) in samples {
+			let _ = writeln!(out, "{name}{labels} {value}");
+		}
+	};
+	let label = |author: &str| format!("{{author=\"{author}\"}}");
+
+	gauge("mblog_current_epoch", "Current epoch.", vec![(String::new(), m.epoch.to_string())]);
+	gauge(
+		"mblog_authority_set_len",
+		"Length of the current Aura authority set.",
+		vec![(String::new(), m.authority_set_len.to_string())],
+	);
+	gauge("mblog_best_block", "Best block number.", vec![(String::new(), m.best_block.to_string())]);
+	gauge(
+		"mblog_finalized_block",
+		"Finalized block number.",
+		vec![(String::new(), m.finalized_block.to_string())],
+	);
+	gauge(
+		"mblog_finalized_head_age_seconds",
+		"Seconds since the finalized head last advanced.",
+		vec![(String::new(), format!("{:.0}", m.finalized_head_age_secs))],
+	);
+	gauge(
+		"mblog_author_in_committee",
+		"1 if the Aura key is in the current authority set.",
+		m.authors.iter().map(|(a, v)| (label(a), u8::from(v.in_committee).to_string())).collect(),
+	);
+	gauge(
+		"mblog_epoch_scheduled_slots",
+		"Slots assigned to the Aura key in the current epoch.",
+		m.authors.iter().map(|(a, v)| (label(a), v.scheduled_slots.to_string())).collect(),
+	);
+	gauge(
+		"mblog_epoch_blocks",
+		"Current-epoch slots of the Aura key by status.",
+		m.authors
+			.iter()
+			.flat_map(|(a, v)| {
+				EXPORTED_STATUSES.iter().map(move |status| {
+					let n = v.blocks_by_status.get(*status).copied().unwrap_or(0);
+					(format!("{{author=\"{a}\",status=\"{status}\"}}"), n.to_string())
+				})
+			})
+			.collect(),
+	);
+	gauge(
+		"mblog_next_slot_seconds",
+		"Seconds until the next scheduled slot of the current epoch.",
+		m.authors
+			.iter()
+			.filter_map(|(a, v)| v.next_slot_seconds.map(|s| (label(a), format!("{s:.0}"))))
+			.collect(),
+	);
+	gauge(
+		"mblog_finality_lag_seconds",
+		"Current-epoch time from seeing our block to seeing it finalized, by quantile.",
+		m.authors
+			.iter()
+			.flat_map(|(a, v)| {
+				v.finality_lag_secs
+					.iter()
+					.map(move |(q, s)| (format!("{{author=\"{a}\",quantile=\"{q}\"}}"), format!("{s:.3}")))
+			})
+			.collect(),
+	);
+	gauge(
+		"mblog_registration_valid",
+		"1 if the validator registration is valid (Ariadne).",
+		m.authors
+			.iter()
+			.filter_map(|(a, v)| v.registration_valid.map(|r| (label(a), u8::from(r).to_string())))
+			.collect(),
+	);
+	gauge(
+		"mblog_stake_lovelace",
+		"Delegated ADA stake in lovelace (Ariadne).",
+		m.authors
+			.iter()
+			.filter_map(|(a, v)| v.stake_lovelace.map(|l| (label(a), l.to_string())))
+			.collect(),
+	);
+	out
+}
+
+/// Bind `addr` and serve `GET /metrics` from a background thread.
+pub fn serve_metrics(addr: &str, metrics: SharedMetrics) -> anyhow::Result<()> {
+	let listener = TcpListener::bind(addr).map_err(|e| anyhow!("failed to bind --metrics-addr '{addr}': {e}"))?;
+	std::thread::spawn(move || {
+		for stream in listener.incoming().flatten() {
+			let _ = handle_request(stream, &metrics);
+		}
+	});
+	Ok(())
+}
+
+fn handle_request(mut stream: TcpStream, metrics: &SharedMetrics) -> std::io::Result<()> {
+	stream.set_read_timeout(Some(CLIENT_IO_TIMEOUT))?;
+	stream.set_write_timeout(Some(CLIENT_IO_TIMEOUT))?;
+	let mut request_line = String::new();
+	BufReader::new(&stream).read_line(&mut request_line)?;
+	let path = request_line.split_whitespace().nth(1).unwrap_or("");
+	let (status, body) = if path == "/metrics" || path.starts_with("/metrics?") {
+		let snapshot = metrics.lock().map(|m| m.clone()).unwrap_or_default();
+		("200 OK", render_metrics(&snapshot))
+	} else {
+		("404 Not Found", "not found\n".to_string())
+	};
+	write!(
+		stream,
+		"HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+		body.len()
+	)
+}
@@ -0,0 +1,273 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use std::sync::mpsc::{channel, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// First retry delay; doubled on every further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventKind {
+	/// Slots were assigned to the key for an epoch.
+	Schedule,
+	Mint,
+	Finality,
+	Missed,
+	Orphaned,
+	/// The key dropped out of `Aura.Authorities`.
+	LeftCommittee,
+	/// The Ariadne registration check flipped from valid to invalid.
+	RegistrationInvalid,
+	/// A scheduled slot is within a `--remind` lead time.
+	Reminder,
+}
+
+impl EventKind {
+	pub fn as_str(self) -> &'static str {
+		match self {
+			EventKind::Schedule => "schedule",
+			EventKind::Mint => "mint",
+			EventKind::Finality => "finality",
+			EventKind::Missed => "missed",
+			EventKind::Orphaned => "orphaned",
+			EventKind::LeftCommittee => "left_committee",
+			EventKind::RegistrationInvalid => "registration_invalid",
+			EventKind::Reminder => "reminder",
+		}
+	}
+
+	/// Event for a `blocks.status` value (`schedule` is not a transition worth notifying).
+	pub fn from_block_status(status: &str) -> Option<Self> {
+		match status {
+			"mint" => Some(EventKind::Mint),
+			"finality" => Some(EventKind::Finality),
+			"missed" => Some(EventKind::Missed),
+			"orphaned" => Some(EventKind::Orphaned),
+			_ => None,
+		}
+	}
+}
+
+/// One webhook notification.
+#[derive(Clone, Debug)]
+pub struct Event {
+	pub kind: EventKind,
+	/// Aura public key (0x hex).
+	pub author: String,
+	pub epoch: u64,
+	pub slot: Option<u64>,
+	pub block_number: Option<u64>,
+	pub block_hash: Option<String>,
+	/// Human-readable one-line summary.
+	pub message: String,
+	pub time_utc: String,
+}
+
+impl Event {
+	pub fn new(kind: EventKind, author: &str, epoch: u64, message: String) -> Self {
+		Self {
+			kind,
+			author: author.to_string(),
+			epoch,
+			slot: None,
+			block_number: None,
+			block_hash: None,
+			message,
+			time_utc: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+		}
+	}
+
+	/// Default JSON payload.
+	pub fn to_json(&self) -> Value {
+		serde_json::json!({
+			"event": self.kind.as_str(),
+			"author": self.author,
+			"epoch": self.epoch,
+			"slot": self.slot,
+			"block_number": self.block_number,
+			"block_hash": self.block_hash,
+			"message": self.message,
+			"time_utc": self.time_utc,
+		})
+	}
+}
+
+/// Webhook target and body template.
+#[derive(Clone)]
+pub struct WebhookConfig {
+	pub url: String,
+	/// Body template with `{{event}}`, `{{author}}`, `{{epoch}}`, `{{slot}}`, `{{block_number}}`,
+	/// `{{block_hash}}`, `{{message}}` and `{{time_utc}}` placeholders; `None` posts [`Event::to_json`].
+	pub template: Option<String>,
+	/// Attempts after the first failed one.
+	pub retries: u32,
+}
+
+/// Request body for `event`. Placeholder values are JSON-string escaped (without the quotes), so a
+/// template such as `{"text": "{{message}}"}` stays valid JSON; unknown values render empty.
+pub fn render_body(template: Option<&str>, event: &Event) -> String {
+	let Some(template) = template else {
+		return event.to_json().to_string();
+	};
+	let escape = |s: &str| {
+		let quoted = Value::String(s.to_string()).to_string();
+		quoted[1..quoted.len() - 1].to_string()
+	};
+	let opt = |v: Option<u64>| v.map(|n| n.to_string()).unwrap_or_default();
+	[
+		("event", event.kind.as_str().to_string()),
+		("author", event.author.clone()),
+		("epoch", event.epoch.to_string()),
+		("slot", opt(event.slot)),
+		("block_number", opt(event.block_number)),
+		("block_hash", event.block_hash.clone().unwrap_or_default()),
+		("message", event.message.clone()),
+		("time_utc", event.time_utc.clone()),
+	]
+	.iter()
+	.fold(template.to_string(), |body, (name, value)| body.replace(&format!("{{{{{name}}}}}"), &escape(value)))
+}
+
+/// POST `event` to the webhook, retrying with exponential backoff.
+pub fn deliver(client: &reqwest::blocking::Client, config: &WebhookConfig, event: &Event) -> anyhow::Result<()> {
+	let body = render_body(config.template.as_deref(), event);
+	let mut delay = RETRY_BASE_DELAY;
+	let mut attempt = 0;
+	loop {
+		let res = client
+			.post(&config.url)
+			.header(reqwest::header::CONTENT_TYPE, "application/json")
+			.body(body.clone())
+			.send()
+			.and_then(|r| r.error_for_status());
+		match res {
+			Ok(_) => return Ok(()),
+			Err(e) if attempt >= config.retries => {
+				return Err(anyhow!("{} attempt(s) failed: {e}", attempt + 1));
+			}
+			Err(_) => {
+				std::thread::sleep(delay);
+				delay = (delay * 2).min(RETRY_MAX_DELAY);
+				attempt += 1;
+			}
+		}
+	}
+}
+
+/// Delivers events from a background thread so retries never stall the watch loop.
+/// Dropping the notifier waits for the queued events to be delivered.
+pub struct Notifier {
+	tx: Option<Sender<Event>>,
+	worker: Option<JoinHandle<()>>,
+}
+
+impl Notifier {
+	pub fn spawn(config: WebhookConfig) -> anyhow::Result<Self> {
+		let client = reqwest::blocking::Client::builder()
+			.user_agent(format!("mblog/{}", env!("CARGO_PKG_VERSION")))
+			.timeout(Duration::from_secs(10))
+			.build()
+			.map_err(|e| anyhow!("{e:?}"))?;
+		let (tx, rx) = channel::<Event>();
+		let worker = std::thread::spawn(move || {
+			for event in rx {
+				if let Err(e) = deliver(&client, &config, &event) {
+					eprintln!("webhook delivery failed ({}): {e}", event.kind.as_str());
+				}
+			}
+		});
+		Ok(Self { tx: Some(tx), worker: Some(worker) })
+	}
+
+	pub fn notify(&self, event: Event) {
+		if let Some(tx) = self.tx.as_ref() {
+			let _ = tx.send(event);
+		}
+	}
+}
+
+impl Drop for Notifier {
+	fn drop(&mut self) {
+		self.tx.take();
+		if let Some(worker) = self.worker.take() {
+			let _ = worker.join();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{BufRead, BufReader, Read, Write};
+	use std::net::TcpListener;
+
+	/// Webhook stand-in answering one request per status in `statuses`; the handle yields the bodies received.
+	fn serve(statuses: &[u16]) -> (String, JoinHandle<Vec<String>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/hook", listener.local_addr().unwrap());
+		let statuses = statuses.to_vec();
+		let handle = std::thread::spawn(move || {
+			let mut bodies = Vec::new();
+			for status in statuses {
+				let (mut stream, _) = listener.accept().unwrap();
+				let mut reader = BufReader::new(&stream);
+				let mut content_length = 0;
+				loop {
+					let mut line = String::new();
+					reader.read_line(&mut line).unwrap();
+					if line == "\r\n" {
+						break;
+					}
+					if let Some((name, value)) = line.split_once(':')
+						&& name.eq_ignore_ascii_case("content-length")
+					{
+						content_length = value.trim().parse().unwrap();
+					}
+				}
+				let mut body = vec![0; content_length];
+				reader.read_exact(&mut body).unwrap();
+				bodies.push(String::from_utf8(body).unwrap());
+				write!(stream, "HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+			}
+			bodies
+		});
+		(url, handle)
+	}
+
+	fn event() -> Event {
+		let mut event = Event::new(EventKind::Missed, "0xaa", 7, "slot 8402 \"missed\"\n\\ retry".to_string());
+		event.slot = Some(8402);
+		event
+	}
+
+	#[test]
+	fn template_values_are_json_escaped() {
+		let event = event();
+		let body = render_body(Some(r#"{"text":"{{message}}","slot":"{{slot}}","hash":"{{block_hash}}"}"#), &event);
+		let value: Value = serde_json::from_str(&body).unwrap();
+		assert_eq!(value["text"], event.message);
+		assert_eq!(value["slot"], "8402");
+		assert_eq!(value["hash"], "");
+	}
+
+	#[test]
+	fn server_error_is_retried() {
+		let (url, server) = serve(&[500, 200]);
+		let config = WebhookConfig { url, template: Some(r#"{"text":"{{message}}"}"#.to_string()), retries: 3 };
+		deliver(&reqwest::blocking::Client::new(), &config, &event()).unwrap();
+		let bodies = server.join().unwrap();
+		assert_eq!(bodies.len(), 2);
+		assert_eq!(bodies[0], bodies[1]);
+	}
+
+	#[test]
+	fn no_retries_fails_after_one_attempt() {
+		let (url, server) = serve(&[500]);
+		let config = WebhookConfig { url, template: None, retries: 0 };
+		let err = deliver(&reqwest::blocking::Client::new(), &config, &event()).unwrap_err();
+		assert!(err.to_string().starts_with("1 attempt(s) failed"), "{err}");
+		assert_eq!(server.join().unwrap().len(), 1);
+	}
+}
@@ -0,0 +1,89 @@
+use anyhow::anyhow;
+use serde_json::Value;
+
+pub fn jsonrpc_http_call(
+	client: &reqwest::blocking::Client,
+	endpoint: &str,
+	method: &str,
+	params: Value,
+) -> anyhow::Result<Value> {
+	let request_body = serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": method,
+		"params": params,
+	});
+	let res: Value = client
+		.post(endpoint)
+		.json(&request_body)
+		.send()?
+		.error_for_status()?
+		.json()?;
+	if let Some(err) = res.get("error") {
+		return Err(anyhow!("JSON-RPC error: {}", serde_json::to_string(err)?));
+	}
+	Ok(res
+		.get("result")
+		.cloned()
+		.unwrap_or(Value::Null))
+}
+
+pub fn parse_lovelace(v: &Value) -> Option<u128> {
+	match v {
+		Value::Number(n) => n.as_u64().map(|u| u as u128),
+		Value::String(s) => s.parse::<u128>().ok(),
+		_ => None,
+	}
+}
+
+pub fn format_ada_from_lovelace(lovelace: u128) -> String {
+	const UNIT: u128 = 1_000_000;
+	let whole = lovelace / UNIT;
+	let frac = (lovelace % UNIT) as u64;
+	if frac == 0 {
+		return whole.to_string();
+	}
+	let mut frac_s = format!("{frac:06}");
+	while frac_s.ends_with('0') {
+		frac_s.pop();
+	}
+	format!("{whole}.{frac_s}")
+}
+
+pub fn fetch_registration_status(
+	client: &reqwest::blocking::Client,
+	endpoint: &str,
+	sidechain_pubkey: &str,
+	mainchain_epoch: u64,
+) -> anyhow::Result<(u128, bool)> {
+	let ariadne = jsonrpc_http_call(
+		client,
+		endpoint,
+		"sidechain_getAriadneParameters",
+		Value::Array(vec![Value::Number(mainchain_epoch.into())]),
+	)?;
+
+	let Some(regs) = ariadne
+		.get("candidateRegistrations")
+		.and_then(|v| v.as_object())
+	else {
+		return Ok((0, false));
+	};
+
+	for (_mainchain_pubkey, entries) in regs {
+		let Some(arr) = entries.as_array() else { continue };
+		for entry in arr {
+			let sc = entry.get("sidechainPubKey").and_then(|v| v.as_str());
+			if sc != Some(sidechain_pubkey) {
+				continue;
+			}
+			let stake = entry
+				.get("stakeDelegation")
+				.and_then(parse_lovelace)
+				.unwrap_or(0);
+			let is_valid = entry.get("isValid").and_then(|v| v.as_bool()).unwrap_or(false);
+			return Ok((stake, is_valid));
+		}
+	}
+	Ok((0, false))
+}
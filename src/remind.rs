@@ -0,0 +1,155 @@
+use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::render::parse_rfc3339_utc;
+
+/// Parse a reminder lead time: `90s`, `10m`, `1h`, or plain seconds.
+pub fn parse_lead_time(s: &str) -> anyhow::Result<u64> {
+	let s = s.trim();
+	let (num, unit) = match s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+		Some((i, _)) => s.split_at(i),
+		None => (s, "s"),
+	};
+	let n: u64 = num.parse().map_err(|_| anyhow!("invalid lead time '{s}' (expected e.g. 90s, 10m, 1h)"))?;
+	let secs = match unit {
+		"s" => Some(n),
+		"m" => n.checked_mul(60),
+		"h" => n.checked_mul(3600),
+		_ => return Err(anyhow!("invalid lead time '{s}' (expected e.g. 90s, 10m, 1h)")),
+	};
+	let Some(secs) = secs.filter(|&secs| lead_delta(secs).is_some()) else {
+		return Err(anyhow!("lead time out of range: '{s}'"));
+	};
+	if secs == 0 {
+		return Err(anyhow!("lead time must be positive: '{s}'"));
+	}
+	Ok(secs)
+}
+
+fn lead_delta(lead_secs: u64) -> Option<TimeDelta> {
+	i64::try_from(lead_secs).ok().and_then(TimeDelta::try_seconds)
+}
+
+/// A reminder that is due: `lead_secs` before the planned time of `slot`.
+pub struct DueReminder {
+	pub slot: u64,
+	pub planned_time_utc: String,
+	pub lead_secs: u64,
+	/// Seconds actually left until the planned time (less than `lead_secs` when mblog woke up late).
+	pub seconds_left: i64,
+}
+
+/// Fires every lead time once per planned slot.
+pub struct Reminders {
+	/// Lead times in seconds, longest first.
+	leads: Vec<u64>,
+	/// `(slot, lead_secs)` already fired (or skipped because a shorter lead was due too).
+	fired: BTreeSet<(u64, u64)>,
+}
+
+impl Reminders {
+	pub fn new(mut leads: Vec<u64>) -> Self {
+		leads.sort_unstable_by(|a, b| b.cmp(a));
+		leads.dedup();
+		Self { leads, fired: BTreeSet::new() }
+	}
+
+	/// Reminders due at `now` for `planned` `(slot, planned_time_utc)` pairs. When several lead times are
+	/// already due for one slot (e.g. right after startup), only the shortest fires.
+	pub fn due(&mut self, planned: &[(u64, String)], now: DateTime<Utc>) -> Vec<DueReminder> {
+		let upcoming: Vec<(u64, &String, DateTime<Utc>)> = planned
+			.iter()
+			.filter_map(|(slot, ts)| parse_rfc3339_utc(ts).map(|t| (*slot, ts, t)))
+			.filter(|(_, _, t)| *t > now)
+			.collect();
+		self.fired.retain(|(slot, _)| upcoming.iter().any(|(s, _, _)| s == slot));
+
+		let mut out = Vec::new();
+		for (slot, ts, t) in upcoming {
+			let seconds_left = (t - now).num_seconds();
+			let due: Vec<u64> = self
+				.leads
+				.iter()
+				.copied()
+				.filter(|lead| seconds_left as u64 <= *lead && !self.fired.contains(&(slot, *lead)))
+				.collect();
+			let Some(&shortest) = due.last() else {
+				continue;
+			};
+			self.fired.extend(due.iter().map(|lead| (slot, *lead)));
+			out.push(DueReminder { slot, planned_time_utc: ts.clone(), lead_secs: shortest, seconds_left });
+		}
+		out
+	}
+
+	/// Time until the next reminder for `planned` becomes due, if any is left.
+	pub fn next_due_in(&self, planned: &[(u64, String)], now: DateTime<Utc>) -> Option<Duration> {
+		planned
+			.iter()
+			.filter_map(|(slot, ts)| parse_rfc3339_utc(ts).map(|t| (*slot, t)))
+			.filter(|(_, t)| *t > now)
+			.flat_map(|(slot, t)| {
+				self.leads
+					.iter()
+					.filter(move |lead| !self.fired.contains(&(slot, **lead)))
+					// A lead reaching back past the representable range is simply due now.
+					.map(move |lead| lead_delta(*lead).and_then(|d| t.checked_sub_signed(d)).unwrap_or(now))
+			})
+			.min()
+			.map(|at| (at - now).to_std().unwrap_or(Duration::ZERO))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn planned(slot: u64, t: DateTime<Utc>) -> Vec<(u64, String)> {
+		vec![(slot, t.to_rfc3339())]
+	}
+
+	#[test]
+	fn lead_time_units_and_errors() {
+		assert_eq!(parse_lead_time("90").unwrap(), 90);
+		assert_eq!(parse_lead_time("90s").unwrap(), 90);
+		assert_eq!(parse_lead_time("10m").unwrap(), 600);
+		assert_eq!(parse_lead_time(" 1h ").unwrap(), 3600);
+		for bad in ["", "0s", "10d", "m", "-5s", "200000000000000m", "99999999999999999999h"] {
+			assert!(parse_lead_time(bad).is_err(), "{bad}");
+		}
+	}
+
+	#[test]
+	fn each_lead_fires_once() {
+		let now = Utc::now();
+		let slot_at = now + TimeDelta::minutes(30);
+		let mut reminders = Reminders::new(vec![60, 600, 3600]);
+
+		// Started (or restarted) inside two leads: only the shortest due one fires.
+		let due = reminders.due(&planned(7, slot_at), now);
+		assert_eq!(due.iter().map(|d| d.lead_secs).collect::<Vec<_>>(), vec![3600]);
+		assert!(reminders.due(&planned(7, slot_at), now).is_empty());
+		assert_eq!(reminders.next_due_in(&planned(7, slot_at), now), Some(Duration::from_secs(20 * 60)));
+
+		let later = slot_at - TimeDelta::seconds(30);
+		let due = reminders.due(&planned(7, slot_at), later);
+		assert_eq!(due.iter().map(|d| d.lead_secs).collect::<Vec<_>>(), vec![60]);
+		assert_eq!(due[0].seconds_left, 30);
+		assert!(reminders.due(&planned(7, slot_at), later).is_empty());
+		assert_eq!(reminders.next_due_in(&planned(7, slot_at), later), None);
+	}
+
+	#[test]
+	fn huge_lead_does_not_panic() {
+		let now = Utc::now();
+		let slot_at = now + TimeDelta::minutes(5);
+		let lead = parse_lead_time("9000000000000000s").unwrap();
+		let mut reminders = Reminders::new(vec![lead, u64::MAX]);
+		assert_eq!(reminders.next_due_in(&planned(7, slot_at), now), Some(Duration::ZERO));
+		let due = reminders.due(&planned(7, slot_at), now);
+		assert_eq!(due.len(), 1);
+		assert_eq!(reminders.next_due_in(&planned(7, slot_at), now), None);
+	}
+}
@@ -0,0 +1,333 @@
+use anyhow::anyhow;
+use chrono::{FixedOffset, Local, Utc};
+use std::io::{IsTerminal, Write};
+use unicode_width::UnicodeWidthStr;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ColorMode {
+	Auto,
+	Always,
+	Never,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Lang {
+	Ja,
+	En,
+}
+
+pub struct I18n {
+	lang: Lang,
+}
+
+impl I18n {
+	pub fn new(lang: Lang) -> Self {
+		Self { lang }
+	}
+
+	pub fn pick<'a>(&self, en: &'a str, ja: &'a str) -> &'a str {
+		match self.lang {
+			Lang::En => en,
+			Lang::Ja => ja,
+		}
+	}
+}
+
+pub struct Colors {
+	enabled: bool,
+}
+
+impl Colors {
+	pub fn new(mode: ColorMode) -> Self {
+		let enabled = match mode {
+			ColorMode::Always => true,
+			ColorMode::Never => false,
+			ColorMode::Auto => std::io::stdout().is_terminal(),
+		};
+		Self { enabled }
+	}
+
+	pub fn wrap(&self, s: impl AsRef<str>, code: &str) -> String {
+		let s = s.as_ref();
+		if !self.enabled {
+			return s.to_string();
+		}
+		format!("\x1b[{code}m{s}\x1b[0m")
+	}
+
+	pub fn epoch(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "36") // cyan
+	}
+	pub fn range(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "33") // yellow
+	}
+	pub fn author(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "35") // magenta
+	}
+	pub fn slot(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "34") // blue
+	}
+	pub fn time(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "32") // green
+	}
+	pub fn dim(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "90") // bright black
+	}
+
+	pub fn error(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "31") // red
+	}
+
+	pub fn ok(&self, v: impl AsRef<str>) -> String {
+		self.wrap(v, "32") // green
+	}
+}
+
+pub fn render_progress_bar(percent: u8, width: usize) -> String {
+	let percent = percent.min(100) as usize;
+	let filled = (percent * width) / 100;
+	let empty = width.saturating_sub(filled);
+	format!("[{}{}]", "=".repeat(filled), " ".repeat(empty))
+}
+
+pub fn print_progress(
+	is_tty: bool,
+	colors: &Colors,
+	label: &str,
+	percent: u8,
+	current_slot: u64,
+	end_slot: u64,
+) {
+	let bar = render_progress_bar(percent, 30);
+	let line = format!(
+		"{label} {} {}% (slot {}/{})",
+		colors.dim(bar),
+		colors.epoch(percent.to_string()),
+		colors.range(current_slot.to_string()),
+		colors.range(end_slot.to_string())
+	);
+
+	if is_tty {
+		print!("\r\x1b[2K{line}");
+		let _ = std::io::stdout().flush();
+	} else {
+		println!("{line}");
+	}
+}
+
+pub enum OutputTz {
+	Utc,
+	Local,
+	/// Local time, but forced via TZ environment (Unix).
+	ForcedLocal,
+	Fixed(FixedOffset),
+}
+
+pub fn parse_output_tz(s: &str) -> anyhow::Result<OutputTz> {
+	let s = s.trim();
+	if s.eq_ignore_ascii_case("utc") {
+		return Ok(OutputTz::Utc);
+	}
+	if s.eq_ignore_ascii_case("local") {
+		return Ok(OutputTz::Local);
+	}
+	// Fixed offset: ±HH:MM
+	let bytes = s.as_bytes();
+	if bytes.len() == 6 && (bytes[0] == b'+' || bytes[0] == b'-') && bytes[3] == b':' {
+		let sign = if bytes[0] == b'+' { 1 } else { -1 };
+		let hh: i32 = s[1..3].parse()?;
+		let mm: i32 = s[4..6].parse()?;
+		if hh > 23 || mm > 59 {
+			return Err(anyhow!("invalid offset '{s}'"));
+		}
+		let secs = sign * (hh * 3600 + mm * 60);
+		let off = FixedOffset::east_opt(secs).ok_or_else(|| anyhow!("invalid offset '{s}'"))?;
+		return Ok(OutputTz::Fixed(off));
+	}
+
+	// IANA timezone like "Asia/Dubai"
+	if s.contains('/') {
+		#[cfg(unix)]
+		{
+			unsafe {
+				std::env::set_var("TZ", s);
+				tzset();
+			}
+			return Ok(OutputTz::ForcedLocal);
+		}
+		#[cfg(not(unix))]
+		{
+			return Err(anyhow!(
+				"--tz '{s}' looks like an IANA zone, but this mode is only supported on Unix"
+			));
+		}
+	}
+
+	Err(anyhow!(
+		"invalid --tz '{s}' (use UTC | local | +HH:MM | -HH:MM | Area/City)"
+	))
+}
+
+pub fn format_ts(ts_ms: i64, tz: &OutputTz) -> String {
+	let dt_utc = chrono::DateTime::<Utc>::from_timestamp_millis(ts_ms).unwrap_or_else(Utc::now);
+	format_dt(dt_utc, tz)
+}
+
+pub fn format_dt(dt_utc: chrono::DateTime<chrono::Utc>, tz: &OutputTz) -> String {
+	match tz {
+		OutputTz::Utc => dt_utc.to_rfc3339(),
+		OutputTz::Local => dt_utc.with_timezone(&Local).to_rfc3339(),
+		OutputTz::ForcedLocal => dt_utc.with_timezone(&Local).to_rfc3339(),
+		OutputTz::Fixed(off) => dt_utc.with_timezone(off).to_rfc3339(),
+	}
+}
+
+pub fn parse_rfc3339_utc(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+	let dt = chrono::DateTime::parse_from_rfc3339(s).ok()?;
+	Some(dt.with_timezone(&Utc))
+}
+
+/// `mblog log --since/--until`: RFC 3339, `YYYY-MM-DD` (UTC midnight), or a relative age such as
+/// `90m`, `12h`, `7d`, `2w` before `now`. Returns RFC 3339 UTC.
+pub fn parse_log_time(s: &str, now: chrono::DateTime<chrono::Utc>, flag: &str) -> anyhow::Result<String> {
+	if let Some(dt) = parse_rfc3339_utc(s) {
+		return Ok(dt.to_rfc3339());
+	}
+	if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+		return Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc().to_rfc3339());
+	}
+	let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+	let secs_per_unit = match unit {
+		"s" => 1,
+		"m" => 60,
+		"h" => 3600,
+		"d" => 86_400,
+		"w" => 7 * 86_400,
+		_ => 0,
+	};
+	num.parse::<i64>()
+		.ok()
+		.filter(|_| secs_per_unit > 0)
+		.and_then(|n| n.checked_mul(secs_per_unit))
+		.and_then(chrono::Duration::try_seconds)
+		.and_then(|ago| now.checked_sub_signed(ago))
+		.map(|dt| dt.to_rfc3339())
+		.ok_or_else(|| anyhow!("invalid {flag} '{s}' (expected RFC 3339, YYYY-MM-DD, or e.g. 7d / 12h)"))
+}
+
+#[cfg(unix)]
+unsafe extern "C" {
+	fn tzset();
+}
+
+pub fn hex32(bytes: [u8; 32]) -> String {
+	format!("0x{}", hex::encode(bytes))
+}
+
+pub fn hex0x(bytes: &[u8]) -> String {
+	format!("0x{}", hex::encode(bytes))
+}
+
+pub fn status_tag(colors: &Colors, status: &str) -> String {
+	match status {
+		"finality" => format!(" {}", colors.ok("finality ✅")),
+		"mint" => format!(" {}", colors.range("mint🆕")),
+		"missed" => format!(" {}", colors.error("missed ❌")),
+		"orphaned" => format!(" {}", colors.error("orphaned 🔀")),
+		_ => format!(" {}", colors.dim("schedule ⏰")),
+	}
+}
+
+pub fn print_kv_table(rows: &[(String, String)]) {
+	let max_w = rows
+		.iter()
+		.map(|(k, _)| UnicodeWidthStr::width(k.as_str()))
+		.max()
+		.unwrap_or(0);
+	for (k, v) in rows {
+		let w = UnicodeWidthStr::width(k.as_str());
+		let pad = max_w.saturating_sub(w);
+		println!("{}{}: {}", " ".repeat(pad), k, v);
+	}
+}
+
+pub fn format_rfc3339_in_tz(s: &str, out_tz: &OutputTz) -> String {
+	let s = s.trim();
+	if s.is_empty() || s == "-" {
+		return "-".to_string();
+	}
+	let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) else {
+		return s.to_string();
+	};
+	let dt_utc = dt.with_timezone(&Utc);
+	match out_tz {
+		OutputTz::Utc => dt_utc.to_rfc3339(),
+		OutputTz::Local | OutputTz::ForcedLocal => dt_utc.with_timezone(&Local).to_rfc3339(),
+		OutputTz::Fixed(off) => dt_utc.with_timezone(off).to_rfc3339(),
+	}
+}
+
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+	let mut widths: Vec<usize> = headers.iter().map(|h| UnicodeWidthStr::width(*h)).collect();
+	for row in rows {
+		for (i, cell) in row.iter().enumerate() {
+			let w = UnicodeWidthStr::width(cell.as_str());
+			if w > widths[i] {
+				widths[i] = w;
+			}
+		}
+	}
+
+	let border = {
+		let mut s = String::new();
+		s.push('|');
+		for w in &widths {
+			s.push_str(&"=".repeat(*w + 2));
+			s.push('|');
+		}
+		s
+	};
+
+	println!("{border}");
+	println!(
+		"|{}|",
+		headers
+			.iter()
+			.enumerate()
+			.map(|(i, h)| format!(" {:<width$} ", *h, width = widths[i]))
+			.collect::<Vec<_>>()
+			.join("|")
+	);
+	println!("{border}");
+	for row in rows {
+		println!(
+			"|{}|",
+			row.iter()
+				.enumerate()
+				.map(|(i, c)| format!(" {:<width$} ", c, width = widths[i]))
+				.collect::<Vec<_>>()
+				.join("|")
+		);
+	}
+	println!("{border}");
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn log_time_formats() {
+		let now = parse_rfc3339_utc("2025-06-10T12:00:00Z").unwrap();
+		let parse = |s: &str| parse_log_time(s, now, "--since");
+		assert_eq!(parse("2025-06-01T09:30:00+09:00").unwrap(), "2025-06-01T00:30:00+00:00");
+		assert_eq!(parse("2025-06-01").unwrap(), "2025-06-01T00:00:00+00:00");
+		assert_eq!(parse("90m").unwrap(), "2025-06-10T10:30:00+00:00");
+		assert_eq!(parse("7d").unwrap(), "2025-06-03T12:00:00+00:00");
+		assert_eq!(parse("2w").unwrap(), "2025-05-27T12:00:00+00:00");
+		for bad in ["", "7", "7y", "d", "99999999999999d", "9999999999999999999d", "999999999999w"] {
+			let err = parse(bad).unwrap_err();
+			assert!(err.to_string().starts_with("invalid --since"), "{bad}: {err}");
+		}
+	}
+}
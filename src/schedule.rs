@@ -0,0 +1,184 @@
+use anyhow::anyhow;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use sp_core::H256;
+use substrate_api_client::ac_primitives::sr25519;
+
+use crate::chain::{authorities_at, fetch_authorities, ChainSource, CommitteeMember};
+use crate::keystore::parse_pubkey_hex;
+use crate::store::{db_chain_epoch_block_hash, db_epoch_aura_keys};
+
+pub fn hash_authorities(auths: &[sr25519::Public]) -> [u8; 32] {
+	let mut hasher = Sha256::new();
+	for a in auths {
+		let bytes: &[u8] = a.as_ref();
+		hasher.update(bytes);
+	}
+	hasher.finalize().into()
+}
+
+pub fn author_in_authorities(author_bytes: &[u8; 32], auths: &[sr25519::Public]) -> bool {
+	auths.iter().any(|a| {
+		let bytes: &[u8] = a.as_ref();
+		bytes == author_bytes.as_slice()
+	})
+}
+
+pub fn schedule_hash(slots: &[u64]) -> [u8; 32] {
+	let mut hasher = Sha256::new();
+	for s in slots {
+		hasher.update(s.to_le_bytes());
+	}
+	hasher.finalize().into()
+}
+
+pub fn compute_my_slots(
+	auths: &[sr25519::Public],
+	author_bytes: &[u8; 32],
+	start_slot: u64,
+	slots_to_scan: u64,
+) -> Vec<u64> {
+	let mut out = Vec::new();
+	if auths.is_empty() {
+		return out;
+	}
+	for i in 0..slots_to_scan {
+		let slot = start_slot + i;
+		let who = &auths[(slot as usize) % auths.len()];
+		let who_bytes: &[u8] = who.as_ref();
+		if who_bytes == author_bytes.as_slice() {
+			out.push(slot);
+		}
+	}
+	out
+}
+
+/// Our slots in an epoch from a `CommitteeInfo` schedule. The committee's Aura keys become
+/// `Aura.Authorities` in order, so slot authorship is the same `slot % len` round-robin.
+pub fn committee_slots(
+	committee: &[CommitteeMember],
+	author_bytes: &[u8; 32],
+	start_slot: u64,
+	slots_to_scan: u64,
+) -> Vec<u64> {
+	let mut out = Vec::new();
+	if committee.is_empty() {
+		return out;
+	}
+	for i in 0..slots_to_scan {
+		let slot = start_slot + i;
+		if committee[(slot as usize) % committee.len()].aura == *author_bytes {
+			out.push(slot);
+		}
+	}
+	out
+}
+
+/// Extrapolate a slot's wall-clock time (ms) from the latest known slot and its timestamp.
+pub fn planned_ts_ms(slot: u64, latest_slot: u64, ts_ms: u64, slot_dur_ms: u64) -> i64 {
+	let delta_slots = slot as i64 - latest_slot as i64;
+	ts_ms as i64 + (delta_slots * slot_dur_ms as i64)
+}
+
+/// Aura keys of an epoch's committee in slot order, and where they were read from: `CommitteeInfo` for the
+/// current or next epoch, else `Aura.Authorities` at the best block (current epoch). Other epochs use the
+/// snapshot recorded in SQLite, else `Aura.Authorities` in the state of a block of the epoch recorded in
+/// `chain_blocks` (needs the node to still hold that state).
+pub fn epoch_committee<C: ChainSource>(
+	src: &C,
+	conn: &Connection,
+	epoch: u64,
+	current_epoch: u64,
+) -> anyhow::Result<(Vec<[u8; 32]>, &'static str)> {
+	let info = if epoch == current_epoch {
+		src.current_committee()
+	} else if epoch == current_epoch + 1 {
+		src.next_committee()
+	} else {
+		Ok(None)
+	};
+	if let Ok(Some((info_epoch, committee))) = info
+		&& info_epoch == epoch
+	{
+		return Ok((committee.iter().map(|m| m.aura).collect(), "committee_info"));
+	}
+	if epoch != current_epoch
+		&& let Some((keys, source)) = db_epoch_aura_keys(conn, epoch)?
+	{
+		let keys = keys
+			.iter()
+			.map(|k| parse_pubkey_hex(k).map_err(|e| anyhow!("invalid Aura key '{k}' in SQLite: {e}")))
+			.collect::<anyhow::Result<Vec<_>>>()?;
+		return Ok((keys, source));
+	}
+	let auths = if epoch == current_epoch {
+		fetch_authorities(src)?
+	} else {
+		let hash = db_chain_epoch_block_hash(conn, epoch)?.ok_or_else(|| {
+			anyhow!("committee of epoch {epoch} is unknown: no block of it is recorded in chain_blocks")
+		})?;
+		let hash: H256 = hash.parse().map_err(|e| anyhow!("invalid block hash '{hash}' in chain_blocks: {e:?}"))?;
+		src.header(hash)?.ok_or_else(|| anyhow!("block {hash:?} not found on the node"))?;
+		// The block's own state: its parent still holds the previous epoch's set if it opens the epoch.
+		authorities_at(src, hash)?
+	};
+	let keys = auths
+		.iter()
+		.filter_map(|a| {
+			let bytes: &[u8] = a.as_ref();
+			<[u8; 32]>::try_from(bytes).ok()
+		})
+		.collect();
+	Ok((keys, "aura_authorities"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chain::scripted::ScriptedChain;
+	use crate::render::hex0x;
+	use crate::store::{db_replace_epoch_authorities, db_upsert_chain_block, ensure_db, ChainBlock};
+
+	fn key(b: u8) -> sr25519::Public {
+		sr25519::Public::from_raw([b; 32])
+	}
+
+	fn member(b: u8) -> CommitteeMember {
+		CommitteeMember { sidechain: vec![b], aura: [b; 32], grandpa: [0; 32] }
+	}
+
+	#[test]
+	fn epoch_committee_sources() {
+		let mut src = ScriptedChain::new(6000, vec![key(1), key(2)]);
+		src.push_block(10);
+		src.set_authorities(vec![key(2), key(1)]);
+		let opening = src.push_block(20);
+		src.set_current_committee(Some((5, vec![member(3), member(4)])));
+		let mut conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+
+		// Current epoch: CommitteeInfo, unless it describes another epoch.
+		assert_eq!(epoch_committee(&src, &conn, 5, 5).unwrap(), (vec![[3; 32], [4; 32]], "committee_info"));
+		assert_eq!(epoch_committee(&src, &conn, 6, 6).unwrap(), (vec![[2; 32], [1; 32]], "aura_authorities"));
+
+		// Past epoch: the SQLite snapshot.
+		db_replace_epoch_authorities(&mut conn, 3, &[hex0x(&[7; 32]), hex0x(&[8; 32])]).unwrap();
+		assert_eq!(epoch_committee(&src, &conn, 3, 5).unwrap(), (vec![[7; 32], [8; 32]], "aura_authorities"));
+
+		// Past epoch without a snapshot: the state of its recorded block, not of the parent.
+		let block = ChainBlock {
+			block_number: 2,
+			block_hash: format!("{opening:?}"),
+			slot: 20,
+			epoch: 2,
+			author: None,
+			timestamp_utc: None,
+			authority_set_len: 2,
+			finalized: true,
+		};
+		db_upsert_chain_block(&conn, &block).unwrap();
+		assert_eq!(epoch_committee(&src, &conn, 2, 5).unwrap(), (vec![[2; 32], [1; 32]], "aura_authorities"));
+
+		assert!(epoch_committee(&src, &conn, 1, 5).is_err());
+	}
+}
@@ -0,0 +1,427 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::render::{hex0x, parse_rfc3339_utc};
+use crate::store::LogRow;
+
+/// Production counters over a set of `blocks` rows.
+#[derive(Default, Clone)]
+pub struct ProductionStats {
+	/// Every scheduled slot, whatever its status.
+	pub scheduled: u64,
+	/// Slots we authored a block for (`mint`, `finality` or `orphaned`).
+	pub minted: u64,
+	pub finalized: u64,
+	pub missed: u64,
+	pub orphaned: u64,
+	/// Still `schedule` (upcoming, or not reached by the finality scan yet).
+	pub pending: u64,
+	/// Longest run of consecutive missed slots of one author.
+	pub longest_miss_streak: u64,
+	/// Runs of two or more consecutive missed slots.
+	pub miss_streaks: u64,
+	delay: Average,
+	/// `onchain_time_utc - slot_start_utc`: how late into its slot our block was authored.
+	lateness: Average,
+	/// `first_seen_utc - onchain_time_utc`: how long the block took to reach mblog.
+	seen_latency: Average,
+	/// `finality_seen_utc - first_seen_utc` of each block, in ms.
+	finality_lags_ms: Vec<i64>,
+}
+
+/// Running mean of millisecond durations.
+#[derive(Default, Clone)]
+struct Average {
+	sum_ms: i64,
+	count: u64,
+}
+
+impl Average {
+	fn add(&mut self, from: Option<&str>, to: Option<&str>) {
+		if let (Some(from), Some(to)) = (from.and_then(parse_rfc3339_utc), to.and_then(parse_rfc3339_utc)) {
+			self.sum_ms += (to - from).num_milliseconds();
+			self.count += 1;
+		}
+	}
+
+	fn secs(&self) -> Option<f64> {
+		(self.count > 0).then(|| self.sum_ms as f64 / self.count as f64 / 1000.0)
+	}
+}
+
+impl ProductionStats {
+	/// Finalized share of the slots that are settled (`finality`, `missed` or `orphaned`).
+	pub fn fill_rate(&self) -> Option<f64> {
+		let settled = self.finalized + self.missed + self.orphaned;
+		(settled > 0).then(|| self.finalized as f64 / settled as f64)
+	}
+
+	/// Average `produced_time_utc - planned_time_utc` in seconds.
+	pub fn avg_delay_secs(&self) -> Option<f64> {
+		self.delay.secs()
+	}
+
+	/// Average `onchain_time_utc - slot_start_utc` in seconds.
+	pub fn avg_lateness_secs(&self) -> Option<f64> {
+		self.lateness.secs()
+	}
+
+	/// Average `first_seen_utc - onchain_time_utc` in seconds.
+	pub fn avg_seen_latency_secs(&self) -> Option<f64> {
+		self.seen_latency.secs()
+	}
+
+	/// Time from seeing our block on the best chain to seeing it final, at quantile `q` (0.0..=1.0), in seconds.
+	pub fn finality_lag_secs(&self, q: f64) -> Option<f64> {
+		let mut lags = self.finality_lags_ms.clone();
+		lags.sort_unstable();
+		quantile(&lags, q).map(|ms| ms as f64 / 1000.0)
+	}
+
+	pub fn avg_finality_lag_secs(&self) -> Option<f64> {
+		let n = self.finality_lags_ms.len();
+		(n > 0).then(|| self.finality_lags_ms.iter().sum::<i64>() as f64 / n as f64 / 1000.0)
+	}
+
+	fn add(&mut self, row: &LogRow) {
+		self.scheduled += 1;
+		match row.status.as_str() {
+			"mint" => self.minted += 1,
+			"finality" => {
+				self.minted += 1;
+				self.finalized += 1;
+			}
+			"orphaned" => {
+				self.minted += 1;
+				self.orphaned += 1;
+			}
+			"missed" => self.missed += 1,
+			_ => self.pending += 1,
+		}
+		if row.status != "missed" {
+			self.delay.add(Some(&row.planned_time_utc), row.produced_time_utc.as_deref());
+			self.lateness.add(row.slot_start_utc.as_deref(), row.onchain_time_utc.as_deref());
+			self.seen_latency.add(row.onchain_time_utc.as_deref(), row.first_seen_utc.as_deref());
+		}
+		let first_seen = row.first_seen_utc.as_deref().and_then(parse_rfc3339_utc);
+		let finality_seen = row.finality_seen_utc.as_deref().and_then(parse_rfc3339_utc);
+		if let (Some(from), Some(to)) = (first_seen, finality_seen) {
+			self.finality_lags_ms.push((to - from).num_milliseconds());
+		}
+	}
+
+	fn end_streak(&mut self, streak: u64) {
+		self.longest_miss_streak = self.longest_miss_streak.max(streak);
+		if streak >= 2 {
+			self.miss_streaks += 1;
+		}
+	}
+
+	pub fn to_json(&self) -> Value {
+		serde_json::json!({
+			"scheduled": self.scheduled,
+			"minted": self.minted,
+			"finalized": self.finalized,
+			"missed": self.missed,
+			"orphaned": self.orphaned,
+			"pending": self.pending,
+			"fill_rate": self.fill_rate(),
+			"longest_miss_streak": self.longest_miss_streak,
+			"miss_streaks": self.miss_streaks,
+			"avg_delay_secs": self.avg_delay_secs(),
+			"avg_lateness_secs": self.avg_lateness_secs(),
+			"avg_seen_latency_secs": self.avg_seen_latency_secs(),
+			"finality_lag_avg_secs": self.avg_finality_lag_secs(),
+			"finality_lag_p50_secs": self.finality_lag_secs(0.5),
+			"finality_lag_p95_secs": self.finality_lag_secs(0.95),
+			"finality_lag_max_secs": self.finality_lag_secs(1.0),
+		})
+	}
+}
+
+/// Nearest-rank quantile of an ascending slice.
+pub fn quantile(sorted: &[i64], q: f64) -> Option<i64> {
+	if sorted.is_empty() {
+		return None;
+	}
+	let rank = (q.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+	Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+/// Per-epoch and overall statistics of `rows` (ordered by slot). Miss streaks are counted per author;
+/// the overall ones may span epoch boundaries, the per-epoch ones restart with each epoch.
+pub fn compute_stats(rows: &[LogRow]) -> (BTreeMap<u64, ProductionStats>, ProductionStats) {
+	let mut epochs: BTreeMap<u64, ProductionStats> = BTreeMap::new();
+	let mut total = ProductionStats::default();
+	// Current miss streak per author, overall and within the current epoch of that author.
+	let mut streaks: HashMap<Option<&str>, (u64, u64, u64)> = HashMap::new();
+
+	for row in rows {
+		let epoch_stats = epochs.entry(row.epoch).or_default();
+		epoch_stats.add(row);
+		total.add(row);
+
+		let (overall, epoch, in_epoch) = streaks.entry(row.author.as_deref()).or_insert((0, row.epoch, 0));
+		if *epoch != row.epoch {
+			if let Some(prev) = epochs.get_mut(epoch) {
+				prev.end_streak(*in_epoch);
+			}
+			*epoch = row.epoch;
+			*in_epoch = 0;
+		}
+		match row.status.as_str() {
+			"missed" => {
+				*overall += 1;
+				*in_epoch += 1;
+			}
+			// Upcoming slots neither break nor extend a streak.
+			"schedule" => {}
+			_ => {
+				total.end_streak(*overall);
+				if let Some(e) = epochs.get_mut(epoch) {
+					e.end_streak(*in_epoch);
+				}
+				*overall = 0;
+				*in_epoch = 0;
+			}
+		}
+	}
+	for (overall, epoch, in_epoch) in streaks.into_values() {
+		total.end_streak(overall);
+		if let Some(e) = epochs.get_mut(&epoch) {
+			e.end_streak(in_epoch);
+		}
+	}
+	(epochs, total)
+}
+
+/// One committee member's production over an epoch, from `chain_blocks`.
+#[derive(Clone)]
+pub struct MemberStats {
+	/// Aura public key (0x hex).
+	pub author: String,
+	/// Positions held in the committee (authority) list.
+	pub seats: u64,
+	/// Slots assigned in the whole epoch.
+	pub assigned: u64,
+	/// Assigned slots covered by the recorded finalized chain (see [`settled_slot_ranges`]).
+	pub settled: u64,
+	/// Finalized blocks authored in the epoch.
+	pub produced: u64,
+}
+
+impl MemberStats {
+	pub fn missed(&self) -> u64 {
+		self.settled.saturating_sub(self.produced)
+	}
+
+	/// Assigned slots outside the settled ranges (upcoming, not final yet, or not recorded).
+	pub fn pending(&self) -> u64 {
+		self.assigned - self.settled
+	}
+
+	pub fn fill_rate(&self) -> Option<f64> {
+		(self.settled > 0).then(|| self.produced.min(self.settled) as f64 / self.settled as f64)
+	}
+
+	pub fn to_json(&self) -> Value {
+		serde_json::json!({
+			"author": self.author,
+			"seats": self.seats,
+			"assigned": self.assigned,
+			"settled": self.settled,
+			"produced": self.produced,
+			"missed": self.missed(),
+			"pending": self.pending(),
+			"fill_rate": self.fill_rate(),
+		})
+	}
+}
+
+/// Slot ranges (inclusive, ascending) of `start_slot..=end_slot` that the recorded finalized chain settles:
+/// the slot of every finalized block, and the slots skipped before it when the previous block (by number)
+/// is recorded too. Slots in a hole of the record are left out, so they are not counted as missed.
+/// `blocks` are `(block_number, slot)` ordered by block number.
+pub fn settled_slot_ranges(blocks: &[(u64, u64)], start_slot: u64, end_slot: u64) -> Vec<(u64, u64)> {
+	let mut ranges: Vec<(u64, u64)> = Vec::new();
+	let mut prev: Option<(u64, u64)> = None;
+	for &(number, slot) in blocks {
+		let first = match prev {
+			Some((prev_number, prev_slot)) if prev_number + 1 == number => prev_slot + 1,
+			_ => slot,
+		};
+		prev = Some((number, slot));
+		let (first, last) = (first.max(start_slot), slot.min(end_slot));
+		if first > last {
+			continue;
+		}
+		match ranges.last_mut() {
+			Some((_, prev_last)) if *prev_last + 1 >= first => *prev_last = (*prev_last).max(last),
+			_ => ranges.push((first, last)),
+		}
+	}
+	ranges
+}
+
+/// Committee leaderboard of an epoch: slots of `committee` (Aura keys in authority order, round-robin
+/// `slot % len`) from `start_slot`, counted as settled inside the `settled` ranges, against the finalized
+/// block counts of `produced`. Best fill rate first.
+pub fn committee_stats(
+	committee: &[[u8; 32]],
+	start_slot: u64,
+	epoch_size: u64,
+	settled: &[(u64, u64)],
+	produced: &HashMap<String, u64>,
+) -> Vec<MemberStats> {
+	let mut members: Vec<MemberStats> = Vec::new();
+	let mut index: HashMap<[u8; 32], usize> = HashMap::new();
+	for aura in committee {
+		let i = *index.entry(*aura).or_insert_with(|| {
+			let author = hex0x(aura);
+			members.push(MemberStats {
+				produced: produced.get(&author).copied().unwrap_or(0),
+				author,
+				seats: 0,
+				assigned: 0,
+				settled: 0,
+			});
+			members.len() - 1
+		});
+		members[i].seats += 1;
+	}
+	if !committee.is_empty() {
+		for slot in start_slot..start_slot + epoch_size {
+			let member = &mut members[index[&committee[(slot as usize) % committee.len()]]];
+			member.assigned += 1;
+			if settled.iter().any(|(first, last)| (*first..=*last).contains(&slot)) {
+				member.settled += 1;
+			}
+		}
+	}
+	members.sort_by(|a, b| {
+		let rate = |m: &MemberStats| m.fill_rate().unwrap_or(-1.0);
+		rate(b).total_cmp(&rate(a)).then(b.produced.cmp(&a.produced)).then(a.author.cmp(&b.author))
+	});
+	members
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn row(slot: u64, epoch: u64, author: &str, status: &str) -> LogRow {
+		LogRow {
+			slot,
+			epoch,
+			status: status.to_string(),
+			author: Some(author.to_string()),
+			planned_time_utc: "2025-01-01T00:00:00+00:00".to_string(),
+			..LogRow::default()
+		}
+	}
+
+	fn streaks(stats: &ProductionStats) -> (u64, u64) {
+		(stats.longest_miss_streak, stats.miss_streaks)
+	}
+
+	#[test]
+	fn miss_streaks_overall_and_per_epoch() {
+		let rows = [
+			row(1, 0, "a", "finality"),
+			row(2, 0, "a", "missed"),
+			row(3, 0, "a", "missed"),
+			row(4, 1, "a", "missed"),
+			row(5, 1, "a", "finality"),
+			row(6, 1, "a", "missed"),
+			row(7, 1, "a", "missed"),
+			row(8, 1, "a", "missed"),
+		];
+		let (epochs, total) = compute_stats(&rows);
+		assert_eq!(streaks(&total), (3, 2));
+		assert_eq!(streaks(&epochs[&0]), (2, 1));
+		assert_eq!(streaks(&epochs[&1]), (3, 1));
+		assert_eq!((total.scheduled, total.finalized, total.missed), (8, 2, 6));
+		assert_eq!(total.fill_rate(), Some(0.25));
+	}
+
+	#[test]
+	fn miss_streaks_are_per_author() {
+		// b's blocks between a's misses do not break a's streak; upcoming slots neither break nor extend it.
+		let rows = [
+			row(1, 0, "a", "missed"),
+			row(2, 0, "b", "finality"),
+			row(3, 0, "a", "schedule"),
+			row(4, 0, "b", "missed"),
+			row(5, 0, "a", "missed"),
+			row(6, 0, "b", "finality"),
+			row(7, 0, "a", "finality"),
+		];
+		let (epochs, total) = compute_stats(&rows);
+		assert_eq!(streaks(&total), (2, 1));
+		assert_eq!(streaks(&epochs[&0]), (2, 1));
+		assert_eq!(total.pending, 1);
+	}
+
+	#[test]
+	fn interleaved_authors_across_epoch_boundary() {
+		let rows = [
+			row(10, 0, "a", "missed"),
+			row(11, 0, "b", "finality"),
+			row(12, 1, "b", "missed"),
+			row(13, 1, "a", "missed"),
+			row(14, 1, "b", "missed"),
+		];
+		let (epochs, total) = compute_stats(&rows);
+		// a: 10 and 13 form one run overall, split in two per epoch; b: 12 and 14 in epoch 1.
+		assert_eq!(streaks(&total), (2, 2));
+		assert_eq!(streaks(&epochs[&0]), (1, 0));
+		assert_eq!(streaks(&epochs[&1]), (2, 1));
+	}
+
+	#[test]
+	fn latencies_and_quantiles() {
+		assert_eq!(quantile(&[], 0.5), None);
+		let sorted: Vec<i64> = (1..=10).collect();
+		assert_eq!([0.0, 0.5, 0.95, 1.0].map(|q| quantile(&sorted, q)), [Some(1), Some(5), Some(10), Some(10)]);
+
+		let timed = |slot: u64, seen_ms: u32, final_s: u32| LogRow {
+			slot_start_utc: Some("2025-01-01T00:00:00Z".to_string()),
+			onchain_time_utc: Some("2025-01-01T00:00:01Z".to_string()),
+			first_seen_utc: Some(format!("2025-01-01T00:00:01.{seen_ms:03}Z")),
+			finality_seen_utc: Some(format!("2025-01-01T00:00:{final_s:02}Z")),
+			..row(slot, 0, "a", "finality")
+		};
+		// A missed slot has no times and does not count.
+		let (_, total) = compute_stats(&[timed(1, 200, 13), timed(2, 400, 31), row(3, 0, "a", "missed")]);
+		assert_eq!(total.avg_lateness_secs(), Some(1.0));
+		assert_eq!(total.avg_seen_latency_secs(), Some(0.3));
+		assert_eq!(total.finality_lag_secs(0.5), Some(11.8));
+		assert_eq!(total.finality_lag_secs(1.0), Some(29.6));
+	}
+
+	#[test]
+	fn settled_ranges_skip_holes_in_the_record() {
+		// #3..#7 are not recorded; slot 2 was skipped between two consecutive recorded blocks.
+		let blocks = [(1, 0), (2, 1), (3, 3), (7, 8), (8, 9)];
+		assert_eq!(settled_slot_ranges(&blocks, 0, 11), vec![(0, 3), (8, 9)]);
+		// The neighbours outside the epoch only settle the slots skipped at its edges.
+		assert_eq!(settled_slot_ranges(&[(5, 98), (6, 102), (7, 103), (8, 113)], 100, 111), vec![(100, 111)]);
+		assert_eq!(settled_slot_ranges(&[(5, 98), (7, 102)], 100, 111), vec![(102, 102)]);
+	}
+
+	#[test]
+	fn committee_hole_is_not_missed() {
+		let (a, b) = ([1u8; 32], [2u8; 32]);
+		let settled = settled_slot_ranges(&[(1, 0), (2, 1), (3, 3), (7, 8), (8, 9)], 0, 11);
+		let produced = HashMap::from([(hex0x(&a), 2), (hex0x(&b), 3)]);
+		let members = committee_stats(&[a, b], 0, 12, &settled, &produced);
+
+		let b_stats = &members[0];
+		assert_eq!(b_stats.author, hex0x(&b));
+		assert_eq!((b_stats.assigned, b_stats.settled, b_stats.missed(), b_stats.pending()), (6, 3, 0, 3));
+		assert_eq!(b_stats.fill_rate(), Some(1.0));
+		// Slot 2 is a real miss; slots 4 and 6 fall in the hole and stay pending.
+		let a_stats = &members[1];
+		assert_eq!((a_stats.assigned, a_stats.settled, a_stats.missed(), a_stats.pending()), (6, 3, 1, 3));
+	}
+}
@@ -0,0 +1,1174 @@
+use rusqlite::{params, params_from_iter, Connection};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::chain::CommitteeMember;
+use crate::render::hex0x;
+
+/// Schema migrations in order: `MIGRATIONS[i]` upgrades a DB from version `i` to `i + 1`.
+///
+/// DB files from before `schema_version` existed (version 0) may already contain some of these
+/// tables/columns, so every step tolerates objects that are already there.
+const MIGRATIONS: &[fn(&Connection) -> anyhow::Result<()>] = &[
+	migrate_initial_schema,
+	migrate_missed_and_orphaned,
+	migrate_sync_state,
+	migrate_author_columns,
+	migrate_latency_columns,
+	migrate_finality_columns,
+	migrate_chain_blocks,
+	migrate_epoch_committee,
+	migrate_schedule_source,
+];
+
+/// Schema version written by this build.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Create or upgrade the schema. Refuses to touch a DB written by a newer mblog.
+pub fn ensure_db(conn: &Connection) -> anyhow::Result<()> {
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+  version INTEGER PRIMARY KEY,
+  applied_at_utc TEXT NOT NULL
+);
+"#,
+	)?;
+	let current = db_schema_version(conn)?;
+	if current > SCHEMA_VERSION {
+		return Err(anyhow::anyhow!(
+			"DB schema version {current} is newer than this mblog supports ({SCHEMA_VERSION}); upgrade mblog"
+		));
+	}
+	for (i, migrate) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+		let version = i as u32 + 1;
+		let tx = conn.unchecked_transaction()?;
+		migrate(&tx).map_err(|e| anyhow::anyhow!("DB migration to schema version {version} failed: {e}"))?;
+		tx.execute(
+			"INSERT INTO schema_version(version, applied_at_utc) VALUES (?1, ?2)",
+			params![version, chrono::Utc::now().to_rfc3339()],
+		)?;
+		tx.commit()?;
+	}
+	Ok(())
+}
+
+/// Whether `e` was raised by SQLite, as opposed to the node RPC the same code path talks to.
+pub fn is_store_error(e: &anyhow::Error) -> bool {
+	e.chain().any(|cause| cause.is::<rusqlite::Error>())
+}
+
+pub fn db_schema_version(conn: &Connection) -> anyhow::Result<u32> {
+	let v: Option<i64> = conn.query_row("SELECT MAX(version) FROM schema_version", [], |r| r.get(0))?;
+	Ok(v.unwrap_or(0) as u32)
+}
+
+fn migrate_initial_schema(conn: &Connection) -> anyhow::Result<()> {
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS epoch_info (
+  epoch INTEGER PRIMARY KEY,
+  start_slot INTEGER NOT NULL,
+  end_slot INTEGER NOT NULL,
+  authority_set_hash TEXT NOT NULL,
+  authority_set_len INTEGER NOT NULL,
+  created_at_utc TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS blocks (
+  slot INTEGER PRIMARY KEY,
+  epoch INTEGER NOT NULL,
+  planned_time_utc TEXT NOT NULL,
+  block_number INTEGER,
+  block_hash TEXT,
+  produced_time_utc TEXT,
+  status TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocks_epoch ON blocks(epoch);
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_missed_and_orphaned(conn: &Connection) -> anyhow::Result<()> {
+	add_column_if_missing(conn, "blocks", "gap_block_number", "INTEGER")?;
+	add_column_if_missing(conn, "blocks", "gap_author", "TEXT")?;
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS orphaned_blocks (
+  slot INTEGER NOT NULL,
+  epoch INTEGER NOT NULL,
+  block_number INTEGER,
+  block_hash TEXT NOT NULL,
+  canonical_hash TEXT,
+  detected_at_utc TEXT NOT NULL,
+  PRIMARY KEY (slot, block_hash)
+);
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_sync_state(conn: &Connection) -> anyhow::Result<()> {
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS sync_state (
+  cursor TEXT PRIMARY KEY,
+  block_number INTEGER NOT NULL,
+  updated_at_utc TEXT NOT NULL
+);
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_author_columns(conn: &Connection) -> anyhow::Result<()> {
+	add_column_if_missing(conn, "blocks", "author", "TEXT")?;
+	if has_column(conn, "epoch_info", "author")? {
+		return Ok(());
+	}
+	// `author` is part of the primary key, so the table has to be rebuilt; old rows get author ''.
+	conn.execute_batch(
+		r#"
+CREATE TABLE epoch_info_new (
+  epoch INTEGER NOT NULL,
+  author TEXT NOT NULL DEFAULT '',
+  start_slot INTEGER NOT NULL,
+  end_slot INTEGER NOT NULL,
+  authority_set_hash TEXT NOT NULL,
+  authority_set_len INTEGER NOT NULL,
+  created_at_utc TEXT NOT NULL,
+  PRIMARY KEY (epoch, author)
+);
+INSERT INTO epoch_info_new(epoch, author, start_slot, end_slot, authority_set_hash, authority_set_len, created_at_utc)
+SELECT epoch, '', start_slot, end_slot, authority_set_hash, authority_set_len, created_at_utc FROM epoch_info;
+DROP TABLE epoch_info;
+ALTER TABLE epoch_info_new RENAME TO epoch_info;
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_latency_columns(conn: &Connection) -> anyhow::Result<()> {
+	add_column_if_missing(conn, "blocks", "slot_start_utc", "TEXT")?;
+	add_column_if_missing(conn, "blocks", "onchain_time_utc", "TEXT")?;
+	add_column_if_missing(conn, "blocks", "first_seen_utc", "TEXT")?;
+	Ok(())
+}
+
+fn migrate_finality_columns(conn: &Connection) -> anyhow::Result<()> {
+	add_column_if_missing(conn, "blocks", "finality_seen_utc", "TEXT")?;
+	add_column_if_missing(conn, "blocks", "finality_seen_block_number", "INTEGER")?;
+	Ok(())
+}
+
+fn migrate_chain_blocks(conn: &Connection) -> anyhow::Result<()> {
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS chain_blocks (
+  block_hash TEXT PRIMARY KEY,
+  block_number INTEGER NOT NULL,
+  slot INTEGER NOT NULL,
+  epoch INTEGER NOT NULL,
+  author TEXT,
+  timestamp_utc TEXT,
+  authority_set_len INTEGER NOT NULL,
+  finalized INTEGER NOT NULL DEFAULT 0,
+  seen_utc TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_chain_blocks_epoch ON chain_blocks(epoch);
+CREATE INDEX IF NOT EXISTS idx_chain_blocks_slot ON chain_blocks(slot);
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_epoch_committee(conn: &Connection) -> anyhow::Result<()> {
+	conn.execute_batch(
+		r#"
+CREATE TABLE IF NOT EXISTS epoch_authorities (
+  epoch INTEGER NOT NULL,
+  position INTEGER NOT NULL,
+  aura TEXT NOT NULL,
+  PRIMARY KEY (epoch, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_authorities_aura ON epoch_authorities(aura);
+
+CREATE TABLE IF NOT EXISTS epoch_committee (
+  epoch INTEGER NOT NULL,
+  position INTEGER NOT NULL,
+  sidechain_key TEXT NOT NULL,
+  aura TEXT NOT NULL,
+  grandpa TEXT NOT NULL,
+  source TEXT NOT NULL,
+  recorded_at_utc TEXT NOT NULL,
+  PRIMARY KEY (epoch, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_committee_aura ON epoch_committee(aura);
+"#,
+	)?;
+	Ok(())
+}
+
+fn migrate_schedule_source(conn: &Connection) -> anyhow::Result<()> {
+	add_column_if_missing(conn, "blocks", "source", "TEXT")?;
+	Ok(())
+}
+
+/// Attribute rows written before the `author` columns existed to `author` (only meaningful when a
+/// single key has been monitored with this DB).
+pub fn db_claim_legacy_rows(conn: &Connection, author: &str) -> anyhow::Result<()> {
+	conn.execute("UPDATE blocks SET author=?1 WHERE author IS NULL", params![author])?;
+	conn.execute("UPDATE OR IGNORE epoch_info SET author=?1 WHERE author=''", params![author])?;
+	conn.execute("DELETE FROM epoch_info WHERE author=''", [])?;
+	Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> anyhow::Result<bool> {
+	let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+	let mut rows = stmt.query([])?;
+	while let Some(row) = rows.next()? {
+		let name: String = row.get(1)?;
+		if name == column {
+			return Ok(true);
+		}
+	}
+	Ok(false)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> anyhow::Result<()> {
+	if has_column(conn, table, column)? {
+		return Ok(());
+	}
+	conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+	Ok(())
+}
+
+pub fn db_upsert_epoch_info(
+	conn: &Connection,
+	epoch: u64,
+	author: &str,
+	start_slot: u64,
+	end_slot: u64,
+	authority_set_hash: &str,
+	authority_set_len: usize,
+) -> anyhow::Result<()> {
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	conn.execute(
+		r#"
+INSERT INTO epoch_info(epoch, start_slot, end_slot, authority_set_hash, authority_set_len, created_at_utc, author)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+ON CONFLICT(epoch, author) DO UPDATE SET
+  start_slot=excluded.start_slot,
+  end_slot=excluded.end_slot,
+  authority_set_hash=excluded.authority_set_hash,
+  authority_set_len=excluded.authority_set_len,
+  created_at_utc=excluded.created_at_utc
+"#,
+		params![
+			epoch as i64,
+			start_slot as i64,
+			end_slot as i64,
+			authority_set_hash,
+			authority_set_len as i64,
+			now_utc,
+			author
+		],
+	)?;
+	Ok(())
+}
+
+/// `blocks.source` of slots computed from the `Aura.Authorities` round-robin; committee-derived slots use
+/// [`COMMITTEE_SOURCE_CURRENT`] / [`COMMITTEE_SOURCE_NEXT`].
+pub const SCHEDULE_SOURCE_AURA: &str = "aura_authorities";
+
+pub fn db_insert_schedule(
+	conn: &mut Connection,
+	epoch: u64,
+	author: &str,
+	planned: &[(u64, String)],
+	source: &str,
+) -> anyhow::Result<()> {
+	let tx = conn.transaction()?;
+	{
+		let mut stmt = tx.prepare(
+			r#"
+INSERT INTO blocks(slot, epoch, planned_time_utc, status, author, source)
+VALUES (?1, ?2, ?3, 'schedule', ?4, ?5)
+ON CONFLICT(slot) DO UPDATE SET
+  epoch=excluded.epoch,
+  author=excluded.author,
+  planned_time_utc=excluded.planned_time_utc,
+  source=excluded.source,
+  status=CASE
+    WHEN blocks.status IN ('finality','missed','orphaned') THEN blocks.status
+    ELSE excluded.status
+  END
+"#,
+		)?;
+		for (slot, planned_time_utc) in planned {
+			stmt.execute(params![*slot as i64, epoch as i64, planned_time_utc, author, source])?;
+		}
+	}
+	tx.commit()?;
+	Ok(())
+}
+
+/// Once an epoch's actual schedule is stored, drop the slots that were planned from `NextCommittee` but
+/// are not part of it (rows it kept were re-sourced). Produced slots are never dropped.
+pub fn db_drop_stale_next_schedule(conn: &Connection, epoch: u64, author: &str) -> anyhow::Result<usize> {
+	let n = conn.execute(
+		"DELETE FROM blocks WHERE epoch=?1 AND author=?2 AND source=?3 AND status IN ('schedule','missed')",
+		params![epoch as i64, author, COMMITTEE_SOURCE_NEXT],
+	)?;
+	Ok(n)
+}
+
+pub fn db_update_block_status(
+	conn: &Connection,
+	slot: u64,
+	block_number: u64,
+	block_hash: &str,
+	produced_time_utc: &str,
+	status: &str,
+) -> anyhow::Result<()> {
+	conn.execute(
+		r#"
+UPDATE blocks
+SET block_number=?2, block_hash=?3, produced_time_utc=?4, status=?5,
+    first_seen_utc=CASE WHEN block_hash IS ?3 THEN first_seen_utc END
+WHERE slot=?1
+  AND (
+    (?5='mint' AND status='schedule') OR
+    (?5='finality' AND status IN ('schedule','mint'))
+  )
+"#,
+		params![
+			slot as i64,
+			block_number as i64,
+			block_hash,
+			produced_time_utc,
+			status
+		],
+	)?;
+	Ok(())
+}
+
+pub fn db_upsert_minted_block(
+	conn: &Connection,
+	slot: u64,
+	epoch: u64,
+	block_number: u64,
+	block_hash: &str,
+	produced_time_utc: &str,
+	author: &str,
+) -> anyhow::Result<()> {
+	// A different block for the same slot replaced ours on the best chain (reorg); keep the old hash.
+	db_archive_mint_hash(conn, slot, block_hash, None)?;
+
+	// Unlike finality, mint is rare and only relevant to "our" blocks.
+	// If the schedule row is missing for any reason, we still want to record mint.
+	conn.execute(
+		r#"
+INSERT INTO blocks(slot, epoch, planned_time_utc, block_number, block_hash, produced_time_utc, status, author)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'mint', ?7)
+ON CONFLICT(slot) DO UPDATE SET
+  author=excluded.author,
+  first_seen_utc=CASE WHEN blocks.block_hash IS excluded.block_hash THEN blocks.first_seen_utc END,
+  block_number=excluded.block_number,
+  block_hash=excluded.block_hash,
+  produced_time_utc=excluded.produced_time_utc,
+  status='mint'
+WHERE blocks.status IN ('schedule','mint')
+"#,
+		params![
+			slot as i64,
+			epoch as i64,
+			produced_time_utc,
+			block_number as i64,
+			block_hash,
+			produced_time_utc,
+			author
+		],
+	)?;
+	Ok(())
+}
+
+/// Latency timestamps of our block `block_hash` in `slot`: the slot start (`slot * SlotDuration`),
+/// its `Timestamp.Now`, and the local time mblog first saw it on the best chain (kept once set).
+pub fn db_set_block_times(
+	conn: &Connection,
+	slot: u64,
+	block_hash: &str,
+	slot_start_utc: &str,
+	onchain_time_utc: Option<&str>,
+	first_seen_utc: Option<&str>,
+) -> anyhow::Result<()> {
+	conn.execute(
+		r#"
+UPDATE blocks
+SET slot_start_utc=?3,
+    onchain_time_utc=COALESCE(?4, onchain_time_utc),
+    first_seen_utc=COALESCE(first_seen_utc, ?5)
+WHERE slot=?1 AND block_hash=?2
+"#,
+		params![slot as i64, block_hash, slot_start_utc, onchain_time_utc, first_seen_utc],
+	)?;
+	Ok(())
+}
+
+/// Record that our `finality` blocks numbered `from_block..=to_block` were seen final at `seen_utc`,
+/// when the finalized head was `finalized_number`. Rows already stamped keep their first observation.
+pub fn db_mark_finality_seen(
+	conn: &Connection,
+	from_block: u64,
+	to_block: u64,
+	finalized_number: u64,
+	seen_utc: &str,
+) -> anyhow::Result<()> {
+	conn.execute(
+		r#"
+UPDATE blocks
+SET finality_seen_utc=?4, finality_seen_block_number=?3
+WHERE status='finality' AND finality_seen_utc IS NULL AND block_number BETWEEN ?1 AND ?2
+"#,
+		params![from_block as i64, to_block as i64, finalized_number as i64, seen_utc],
+	)?;
+	Ok(())
+}
+
+/// `(author, finality_seen_utc - first_seen_utc in ms)` of our blocks in `epoch`, by slot.
+pub fn db_finality_lags(conn: &Connection, epoch: u64) -> anyhow::Result<Vec<(String, i64)>> {
+	let mut stmt = conn.prepare(
+		r#"
+SELECT COALESCE(author, ''),
+       CAST(ROUND((julianday(finality_seen_utc) - julianday(first_seen_utc)) * 86400000) AS INTEGER)
+FROM blocks
+WHERE epoch=?1 AND first_seen_utc IS NOT NULL AND finality_seen_utc IS NOT NULL
+ORDER BY slot ASC
+"#,
+	)?;
+	let rows = stmt.query_map(params![epoch as i64], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))?;
+	Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Mark scheduled slots strictly between two consecutive finalized blocks as `missed`.
+/// `gap_block_number`/`gap_author` record the finalized block that followed the gap.
+pub fn db_mark_missed(
+	conn: &Connection,
+	after_slot: u64,
+	before_slot: u64,
+	gap_block_number: u64,
+	gap_author: Option<&str>,
+) -> anyhow::Result<usize> {
+	let n = conn.execute(
+		r#"
+UPDATE blocks
+SET status='missed', gap_block_number=?3, gap_author=?4
+WHERE slot > ?1 AND slot < ?2 AND status='schedule'
+"#,
+		params![after_slot as i64, before_slot as i64, gap_block_number as i64, gap_author],
+	)?;
+	Ok(n)
+}
+
+/// Copy the `mint` row's recorded hash for `slot` into `orphaned_blocks` if it differs from `current_hash`.
+/// `canonical_hash` is the finalized block for the slot, when known.
+pub fn db_archive_mint_hash(
+	conn: &Connection,
+	slot: u64,
+	current_hash: &str,
+	canonical_hash: Option<&str>,
+) -> anyhow::Result<usize> {
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	let n = conn.execute(
+		r#"
+INSERT INTO orphaned_blocks(slot, epoch, block_number, block_hash, canonical_hash, detected_at_utc)
+SELECT slot, epoch, block_number, block_hash, ?3, ?4
+FROM blocks
+WHERE slot=?1 AND status='mint' AND block_hash IS NOT NULL AND block_hash<>?2
+ON CONFLICT(slot, block_hash) DO NOTHING
+"#,
+		params![slot as i64, current_hash, canonical_hash, now_utc],
+	)?;
+	Ok(n)
+}
+
+/// Minted blocks whose slot the finalized chain skipped lost their fork: move them to `orphaned`
+/// and keep their hash in `orphaned_blocks`.
+pub fn db_mark_orphaned(conn: &Connection, after_slot: u64, before_slot: u64) -> anyhow::Result<usize> {
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	conn.execute(
+		r#"
+INSERT INTO orphaned_blocks(slot, epoch, block_number, block_hash, canonical_hash, detected_at_utc)
+SELECT slot, epoch, block_number, block_hash, NULL, ?3
+FROM blocks
+WHERE slot > ?1 AND slot < ?2 AND status='mint' AND block_hash IS NOT NULL
+ON CONFLICT(slot, block_hash) DO NOTHING
+"#,
+		params![after_slot as i64, before_slot as i64, now_utc],
+	)?;
+	let n = conn.execute(
+		"UPDATE blocks SET status='orphaned' WHERE slot > ?1 AND slot < ?2 AND status='mint'",
+		params![after_slot as i64, before_slot as i64],
+	)?;
+	Ok(n)
+}
+
+/// `(author, status, count)` of the `blocks` rows in `epoch`.
+pub fn db_epoch_status_counts(conn: &Connection, epoch: u64) -> anyhow::Result<Vec<(String, String, u64)>> {
+	let mut stmt = conn.prepare(
+		"SELECT COALESCE(author, ''), status, COUNT(*) FROM blocks WHERE epoch=?1 GROUP BY author, status",
+	)?;
+	let rows = stmt.query_map(params![epoch as i64], |r| {
+		Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, i64>(2)? as u64))
+	})?;
+	Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Replace the ordered `Aura.Authorities` list (0x hex keys) of an epoch.
+pub fn db_replace_epoch_authorities(conn: &mut Connection, epoch: u64, authorities: &[String]) -> anyhow::Result<()> {
+	let tx = conn.transaction()?;
+	tx.execute("DELETE FROM epoch_authorities WHERE epoch=?1", params![epoch as i64])?;
+	{
+		let mut stmt = tx.prepare("INSERT INTO epoch_authorities(epoch, position, aura) VALUES (?1, ?2, ?3)")?;
+		for (position, aura) in authorities.iter().enumerate() {
+			stmt.execute(params![epoch as i64, position as i64, aura])?;
+		}
+	}
+	tx.commit()?;
+	Ok(())
+}
+
+/// `epoch_committee.source` of a snapshot read from `SessionCommitteeManagement.CurrentCommittee`.
+pub const COMMITTEE_SOURCE_CURRENT: &str = "current_committee";
+/// `epoch_committee.source` of a snapshot read from `SessionCommitteeManagement.NextCommittee`.
+pub const COMMITTEE_SOURCE_NEXT: &str = "next_committee";
+
+/// Replace the committee snapshot of an epoch. A `next_committee` snapshot never replaces a
+/// `current_committee` one, which is what the runtime ended up enforcing.
+pub fn db_replace_epoch_committee(
+	conn: &mut Connection,
+	epoch: u64,
+	source: &str,
+	committee: &[CommitteeMember],
+) -> anyhow::Result<()> {
+	let tx = conn.transaction()?;
+	if source == COMMITTEE_SOURCE_NEXT {
+		let has_current: bool = tx.query_row(
+			"SELECT EXISTS(SELECT 1 FROM epoch_committee WHERE epoch=?1 AND source=?2)",
+			params![epoch as i64, COMMITTEE_SOURCE_CURRENT],
+			|r| r.get(0),
+		)?;
+		if has_current {
+			return Ok(());
+		}
+	}
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	tx.execute("DELETE FROM epoch_committee WHERE epoch=?1", params![epoch as i64])?;
+	{
+		let mut stmt = tx.prepare(
+			"INSERT INTO epoch_committee(epoch, position, sidechain_key, aura, grandpa, source, recorded_at_utc) \
+			 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+		)?;
+		for (position, m) in committee.iter().enumerate() {
+			stmt.execute(params![
+				epoch as i64,
+				position as i64,
+				hex0x(&m.sidechain),
+				hex0x(&m.aura),
+				hex0x(&m.grandpa),
+				source,
+				now_utc
+			])?;
+		}
+	}
+	tx.commit()?;
+	Ok(())
+}
+
+/// Recorded Aura keys of an epoch in slot order, with where they came from: the `epoch_committee`
+/// snapshot (`committee_info`), else `epoch_authorities` (`aura_authorities`).
+pub fn db_epoch_aura_keys(conn: &Connection, epoch: u64) -> anyhow::Result<Option<(Vec<String>, &'static str)>> {
+	for (table, source) in [("epoch_committee", "committee_info"), ("epoch_authorities", "aura_authorities")] {
+		let mut stmt = conn.prepare(&format!("SELECT aura FROM {table} WHERE epoch=?1 ORDER BY position"))?;
+		let keys = stmt.query_map(params![epoch as i64], |r| r.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+		if !keys.is_empty() {
+			return Ok(Some((keys, source)));
+		}
+	}
+	Ok(None)
+}
+
+/// One walked block header of any author (`chain_blocks`).
+pub struct ChainBlock {
+	pub block_number: u64,
+	pub block_hash: String,
+	pub slot: u64,
+	pub epoch: u64,
+	/// Expected Aura author of `slot` (`authorities_at(parent)[slot % len]`).
+	pub author: Option<String>,
+	pub timestamp_utc: Option<String>,
+	pub authority_set_len: usize,
+	pub finalized: bool,
+}
+
+/// Insert a walked header, or mark it finalized when it is seen again by the finality scan.
+pub fn db_upsert_chain_block(conn: &Connection, block: &ChainBlock) -> anyhow::Result<()> {
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	conn.execute(
+		r#"
+INSERT INTO chain_blocks(block_hash, block_number, slot, epoch, author, timestamp_utc, authority_set_len, finalized, seen_utc)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+ON CONFLICT(block_hash) DO UPDATE SET
+  author=COALESCE(excluded.author, chain_blocks.author),
+  timestamp_utc=COALESCE(excluded.timestamp_utc, chain_blocks.timestamp_utc),
+  authority_set_len=MAX(chain_blocks.authority_set_len, excluded.authority_set_len),
+  finalized=MAX(chain_blocks.finalized, excluded.finalized)
+"#,
+		params![
+			block.block_hash,
+			block.block_number as i64,
+			block.slot as i64,
+			block.epoch as i64,
+			block.author,
+			block.timestamp_utc,
+			block.authority_set_len as i64,
+			block.finalized,
+			now_utc
+		],
+	)?;
+	Ok(())
+}
+
+/// `(block_number, slot)` of the finalized blocks of an epoch in `chain_blocks`, plus the finalized
+/// blocks right before and after it (by number) when recorded, ordered by block number.
+pub fn db_chain_finalized_blocks(conn: &Connection, epoch: u64) -> anyhow::Result<Vec<(u64, u64)>> {
+	let mut stmt = conn.prepare(
+		r#"
+SELECT block_number, slot FROM chain_blocks
+WHERE finalized=1
+  AND block_number BETWEEN
+    (SELECT MIN(block_number) - 1 FROM chain_blocks WHERE epoch=?1 AND finalized=1) AND
+    (SELECT MAX(block_number) + 1 FROM chain_blocks WHERE epoch=?1 AND finalized=1)
+ORDER BY block_number
+"#,
+	)?;
+	let rows = stmt.query_map(params![epoch as i64], |r| Ok((r.get::<_, i64>(0)? as u64, r.get::<_, i64>(1)? as u64)))?;
+	Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Finalized blocks of an epoch in `chain_blocks`, per expected author.
+pub fn db_chain_produced_counts(conn: &Connection, epoch: u64) -> anyhow::Result<Vec<(String, u64)>> {
+	let mut stmt = conn.prepare(
+		"SELECT author, COUNT(DISTINCT slot) FROM chain_blocks \
+		 WHERE epoch=?1 AND finalized=1 AND author IS NOT NULL GROUP BY author",
+	)?;
+	let rows = stmt.query_map(params![epoch as i64], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)? as u64)))?;
+	Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Hash of a recorded block of an epoch in `chain_blocks`, preferring the earliest finalized one.
+pub fn db_chain_epoch_block_hash(conn: &Connection, epoch: u64) -> anyhow::Result<Option<String>> {
+	let mut stmt =
+		conn.prepare("SELECT block_hash FROM chain_blocks WHERE epoch=?1 ORDER BY finalized DESC, slot LIMIT 1")?;
+	let mut rows = stmt.query(params![epoch as i64])?;
+	match rows.next()? {
+		Some(row) => Ok(Some(row.get(0)?)),
+		None => Ok(None),
+	}
+}
+
+/// `sync_state` cursor names: last best / finalized block number already scanned by the watch loop.
+pub const SYNC_CURSOR_BEST: &str = "best";
+pub const SYNC_CURSOR_FINALIZED: &str = "finalized";
+
+pub fn db_get_sync_cursor(conn: &Connection, cursor: &str) -> anyhow::Result<Option<u64>> {
+	let mut stmt = conn.prepare("SELECT block_number FROM sync_state WHERE cursor=?1")?;
+	let mut rows = stmt.query(params![cursor])?;
+	match rows.next()? {
+		Some(row) => Ok(Some(row.get::<_, i64>(0)? as u64)),
+		None => Ok(None),
+	}
+}
+
+pub fn db_set_sync_cursor(conn: &Connection, cursor: &str, block_number: u64) -> anyhow::Result<()> {
+	let now_utc = chrono::Utc::now().to_rfc3339();
+	conn.execute(
+		r#"
+INSERT INTO sync_state(cursor, block_number, updated_at_utc)
+VALUES (?1, ?2, ?3)
+ON CONFLICT(cursor) DO UPDATE SET
+  block_number=excluded.block_number,
+  updated_at_utc=excluded.updated_at_utc
+"#,
+		params![cursor, block_number as i64, now_utc],
+	)?;
+	Ok(())
+}
+
+#[derive(Clone)]
+pub struct ScheduleRow {
+	pub slot: u64,
+	pub planned_time_utc: String,
+	pub status: String,
+	pub block_number: Option<u64>,
+	pub block_hash: Option<String>,
+	pub produced_time_utc: Option<String>,
+}
+
+pub fn db_fetch_schedule_rows(conn: &Connection, slots: &[u64]) -> anyhow::Result<Vec<ScheduleRow>> {
+	if slots.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	let placeholders = std::iter::repeat_n("?", slots.len())
+		.collect::<Vec<_>>()
+		.join(",");
+	let sql = format!(
+		"SELECT slot, planned_time_utc, status, block_number, block_hash, produced_time_utc \
+		 FROM blocks WHERE slot IN ({placeholders}) ORDER BY slot ASC"
+	);
+
+	let mut stmt = conn.prepare(&sql)?;
+	let mut rows = stmt.query(params_from_iter(slots.iter().map(|s| *s as i64)))?;
+
+	let mut out: Vec<ScheduleRow> = Vec::new();
+	while let Some(row) = rows.next()? {
+		let slot: i64 = row.get(0)?;
+		let planned_time_utc: String = row.get(1)?;
+		let status: String = row.get(2)?;
+		let block_number: Option<i64> = row.get(3)?;
+		let block_hash: Option<String> = row.get(4)?;
+		let produced_time_utc: Option<String> = row.get(5)?;
+		out.push(ScheduleRow {
+			slot: slot as u64,
+			planned_time_utc,
+			status,
+			block_number: block_number.map(|n| n as u64),
+			block_hash,
+			produced_time_utc,
+		});
+	}
+	Ok(out)
+}
+
+pub fn schedule_rows_hash(rows: &[ScheduleRow]) -> [u8; 32] {
+	let mut hasher = Sha256::new();
+	for r in rows {
+		hasher.update(r.slot.to_le_bytes());
+		hasher.update(r.planned_time_utc.as_bytes());
+		hasher.update(b"\0");
+		hasher.update(r.status.as_bytes());
+		hasher.update(b"\0");
+		if let Some(n) = r.block_number {
+			hasher.update(n.to_le_bytes());
+		}
+		hasher.update(b"\0");
+		if let Some(ref h) = r.block_hash {
+			hasher.update(h.as_bytes());
+		}
+		hasher.update(b"\0");
+		if let Some(ref t) = r.produced_time_utc {
+			hasher.update(t.as_bytes());
+		}
+		hasher.update(b"\0");
+	}
+	hasher.finalize().into()
+}
+
+/// One `blocks` row with the `epoch_info` fields of its epoch, as listed by `mblog log`.
+#[derive(Clone, Default)]
+pub struct LogRow {
+	pub slot: u64,
+	pub epoch: u64,
+	pub status: String,
+	pub author: Option<String>,
+	pub planned_time_utc: String,
+	pub produced_time_utc: Option<String>,
+	pub slot_start_utc: Option<String>,
+	pub onchain_time_utc: Option<String>,
+	pub first_seen_utc: Option<String>,
+	pub finality_seen_utc: Option<String>,
+	pub finality_seen_block_number: Option<u64>,
+	pub block_number: Option<u64>,
+	pub block_hash: Option<String>,
+	pub gap_block_number: Option<u64>,
+	pub gap_author: Option<String>,
+	pub epoch_start_slot: Option<u64>,
+	pub epoch_end_slot: Option<u64>,
+	pub authority_set_hash: Option<String>,
+	pub authority_set_len: Option<u64>,
+	/// First index of `author` in the epoch's `Aura.Authorities` (from `epoch_authorities`).
+	pub authority_position: Option<u64>,
+	/// Positions `author` holds in that list; `None` when the list was not recorded.
+	pub authority_seats: Option<u64>,
+	/// Where the slot assignment came from (`aura_authorities`, `current_committee`, `next_committee`).
+	pub source: Option<String>,
+}
+
+/// Field names of `mblog log --format json|ndjson|csv`, in CSV column order.
+pub const LOG_FIELDS: [&str; 23] = [
+	"epoch",
+	"slot",
+	"slot_in_epoch",
+	"status",
+	"author",
+	"planned_time_utc",
+	"produced_time_utc",
+	"slot_start_utc",
+	"onchain_time_utc",
+	"first_seen_utc",
+	"finality_seen_utc",
+	"finality_seen_block_number",
+	"block_number",
+	"block_hash",
+	"gap_block_number",
+	"gap_author",
+	"epoch_start_slot",
+	"epoch_end_slot",
+	"authority_set_hash",
+	"authority_set_len",
+	"authority_position",
+	"authority_seats",
+	"source",
+];
+
+impl LogRow {
+	pub fn slot_in_epoch(&self) -> Option<u64> {
+		self.epoch_start_slot.map(|s| self.slot.saturating_sub(s))
+	}
+
+	/// Values of [`LOG_FIELDS`], in the same order.
+	pub fn field_values(&self) -> [Value; 23] {
+		[
+			self.epoch.into(),
+			self.slot.into(),
+			self.slot_in_epoch().into(),
+			self.status.clone().into(),
+			self.author.clone().into(),
+			self.planned_time_utc.clone().into(),
+			self.produced_time_utc.clone().into(),
+			self.slot_start_utc.clone().into(),
+			self.onchain_time_utc.clone().into(),
+			self.first_seen_utc.clone().into(),
+			self.finality_seen_utc.clone().into(),
+			self.finality_seen_block_number.into(),
+			self.block_number.into(),
+			self.block_hash.clone().into(),
+			self.gap_block_number.into(),
+			self.gap_author.clone().into(),
+			self.epoch_start_slot.into(),
+			self.epoch_end_slot.into(),
+			self.authority_set_hash.clone().into(),
+			self.authority_set_len.into(),
+			self.authority_position.into(),
+			self.authority_seats.into(),
+			self.source.clone().into(),
+		]
+	}
+
+	/// JSON object keyed by [`LOG_FIELDS`] (`--format json|ndjson`).
+	pub fn to_json(&self) -> Value {
+		Value::Object(LOG_FIELDS.iter().map(|f| f.to_string()).zip(self.field_values()).collect())
+	}
+
+	/// One CSV record in [`LOG_FIELDS`] column order (`--format csv`).
+	pub fn to_csv_record(&self) -> String {
+		self.field_values().iter().map(csv_field).collect::<Vec<_>>().join(",")
+	}
+}
+
+/// RFC 4180 field: nulls are empty, and fields with separators or quotes are quoted.
+fn csv_field(v: &Value) -> String {
+	let s = match v {
+		Value::Null => return String::new(),
+		Value::String(s) => s.clone(),
+		other => other.to_string(),
+	};
+	if s.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", s.replace('"', "\"\""))
+	} else {
+		s
+	}
+}
+
+/// Row filters of `mblog log`; `None`/empty fields do not filter.
+#[derive(Default)]
+pub struct LogFilter {
+	pub from_epoch: Option<u64>,
+	pub to_epoch: Option<u64>,
+	/// Inclusive bounds on `planned_time_utc` (RFC 3339).
+	pub since_utc: Option<String>,
+	pub until_utc: Option<String>,
+	pub statuses: Vec<String>,
+	pub author: Option<String>,
+	pub slots: Vec<u64>,
+	/// Keep only the last N matching rows (by slot).
+	pub last: Option<u64>,
+}
+
+/// Blocks matching `filter`, by slot. `epoch_info` is matched on the block's author, falling back to
+/// any row of the epoch (e.g. blocks stored before the author column).
+pub fn db_fetch_log_rows(conn: &Connection, filter: &LogFilter) -> anyhow::Result<Vec<LogRow>> {
+	use rusqlite::types::Value as SqlValue;
+
+	let mut clauses: Vec<String> = Vec::new();
+	let mut values: Vec<SqlValue> = Vec::new();
+	let mut bind = |clause: &str, value: SqlValue| {
+		values.push(value);
+		clauses.push(clause.replace('?', &format!("?{}", values.len())));
+	};
+	if let Some(e) = filter.from_epoch {
+		bind("b.epoch >= ?", SqlValue::Integer(e as i64));
+	}
+	if let Some(e) = filter.to_epoch {
+		bind("b.epoch <= ?", SqlValue::Integer(e as i64));
+	}
+	if let Some(ref t) = filter.since_utc {
+		bind("julianday(b.planned_time_utc) >= julianday(?)", SqlValue::Text(t.clone()));
+	}
+	if let Some(ref t) = filter.until_utc {
+		bind("julianday(b.planned_time_utc) <= julianday(?)", SqlValue::Text(t.clone()));
+	}
+	if let Some(ref a) = filter.author {
+		bind("b.author = ?", SqlValue::Text(a.clone()));
+	}
+	for (column, list) in [
+		("b.status", filter.statuses.iter().map(|s| SqlValue::Text(s.clone())).collect::<Vec<_>>()),
+		("b.slot", filter.slots.iter().map(|s| SqlValue::Integer(*s as i64)).collect()),
+	] {
+		if list.is_empty() {
+			continue;
+		}
+		let placeholders: Vec<String> = (values.len() + 1..=values.len() + list.len()).map(|i| format!("?{i}")).collect();
+		clauses.push(format!("{column} IN ({})", placeholders.join(",")));
+		values.extend(list);
+	}
+	let where_sql = if clauses.is_empty() { String::new() } else { format!("WHERE {}", clauses.join(" AND ")) };
+	let limit_sql = match filter.last {
+		Some(n) => format!("ORDER BY b.slot DESC LIMIT {n}"),
+		None => String::new(),
+	};
+
+	let sql = format!(
+		r#"
+SELECT * FROM (
+SELECT b.slot, b.epoch, b.status, b.author, b.planned_time_utc, b.produced_time_utc,
+       b.block_number, b.block_hash, b.gap_block_number, b.gap_author,
+       e.start_slot, e.end_slot, e.authority_set_hash, e.authority_set_len,
+       b.slot_start_utc, b.onchain_time_utc, b.first_seen_utc,
+       b.finality_seen_utc, b.finality_seen_block_number,
+       (SELECT MIN(position) FROM epoch_authorities ea WHERE ea.epoch = b.epoch AND ea.aura = b.author),
+       (SELECT SUM(ea.aura = b.author) FROM epoch_authorities ea WHERE ea.epoch = b.epoch),
+       b.source
+FROM blocks b
+LEFT JOIN epoch_info e ON e.rowid = COALESCE(
+  (SELECT rowid FROM epoch_info WHERE epoch = b.epoch AND author = COALESCE(b.author, '')),
+  (SELECT MIN(rowid) FROM epoch_info WHERE epoch = b.epoch)
+)
+{where_sql}
+{limit_sql}
+) ORDER BY slot ASC
+"#
+	);
+	let mut stmt = conn.prepare(&sql)?;
+	let rows = stmt.query_map(params_from_iter(values), |r| {
+		let opt_u64 = |i: usize| r.get::<_, Option<i64>>(i).map(|v| v.map(|n| n as u64));
+		Ok(LogRow {
+			slot: r.get::<_, i64>(0)? as u64,
+			epoch: r.get::<_, i64>(1)? as u64,
+			status: r.get(2)?,
+			author: r.get(3)?,
+			planned_time_utc: r.get(4)?,
+			produced_time_utc: r.get(5)?,
+			block_number: opt_u64(6)?,
+			block_hash: r.get(7)?,
+			gap_block_number: opt_u64(8)?,
+			gap_author: r.get(9)?,
+			epoch_start_slot: opt_u64(10)?,
+			epoch_end_slot: opt_u64(11)?,
+			authority_set_hash: r.get(12)?,
+			authority_set_len: opt_u64(13)?,
+			slot_start_utc: r.get(14)?,
+			onchain_time_utc: r.get(15)?,
+			first_seen_utc: r.get(16)?,
+			finality_seen_utc: r.get(17)?,
+			finality_seen_block_number: opt_u64(18)?,
+			authority_position: opt_u64(19)?,
+			authority_seats: opt_u64(20)?,
+			source: r.get(21)?,
+		})
+	})?;
+	Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn count(conn: &Connection, sql: &str) -> i64 {
+		conn.query_row(sql, [], |r| r.get(0)).unwrap()
+	}
+
+	/// A DB from before `schema_version` existed: the initial tables with a few rows.
+	fn baseline_db() -> Connection {
+		let conn = Connection::open_in_memory().unwrap();
+		migrate_initial_schema(&conn).unwrap();
+		conn.execute_batch(
+			r#"
+INSERT INTO epoch_info VALUES (7, 8400, 9599, 'abcd', 5, '2025-01-01T00:00:00+00:00');
+INSERT INTO blocks(slot, epoch, planned_time_utc, status) VALUES (8402, 7, '2025-01-01T00:00:12+00:00', 'schedule');
+INSERT INTO blocks(slot, epoch, planned_time_utc, block_number, block_hash, produced_time_utc, status)
+VALUES (8407, 7, '2025-01-01T00:00:42+00:00', 100, '0x01', '2025-01-01T00:00:42+00:00', 'finality');
+"#,
+		)
+		.unwrap();
+		conn
+	}
+
+	#[test]
+	fn baseline_db_is_upgraded_and_keeps_rows() {
+		let conn = baseline_db();
+		ensure_db(&conn).unwrap();
+		assert_eq!(db_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM blocks"), 2);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM blocks WHERE author IS NULL"), 2);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM epoch_info WHERE epoch=7 AND author=''"), 1);
+		for table in ["orphaned_blocks", "sync_state", "chain_blocks", "epoch_authorities", "epoch_committee"] {
+			assert_eq!(count(&conn, &format!("SELECT COUNT(*) FROM {table}")), 0);
+		}
+		assert!(has_column(&conn, "blocks", "source").unwrap());
+
+		db_claim_legacy_rows(&conn, "0xaa").unwrap();
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM blocks WHERE author='0xaa'"), 2);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM epoch_info WHERE author=''"), 0);
+		let (author, len): (String, i64) = conn
+			.query_row("SELECT author, authority_set_len FROM epoch_info WHERE epoch=7", [], |r| {
+				Ok((r.get(0)?, r.get(1)?))
+			})
+			.unwrap();
+		assert_eq!((author.as_str(), len), ("0xaa", 5));
+	}
+
+	#[test]
+	fn ensure_db_is_idempotent() {
+		let conn = baseline_db();
+		ensure_db(&conn).unwrap();
+		ensure_db(&conn).unwrap();
+		assert_eq!(db_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM schema_version"), SCHEMA_VERSION as i64);
+		assert_eq!(count(&conn, "SELECT COUNT(*) FROM blocks"), 2);
+	}
+
+	#[test]
+	fn chain_finalized_blocks_include_neighbours() {
+		let conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+		let blocks = [(4, 98, true), (5, 101, true), (6, 102, true), (7, 104, false), (9, 112, true)];
+		for (number, slot, finalized) in blocks {
+			let block = ChainBlock {
+				block_number: number,
+				block_hash: format!("0x{number:02x}"),
+				slot,
+				epoch: slot / 10,
+				author: None,
+				timestamp_utc: None,
+				authority_set_len: 2,
+				finalized,
+			};
+			db_upsert_chain_block(&conn, &block).unwrap();
+		}
+		assert_eq!(db_chain_finalized_blocks(&conn, 10).unwrap(), vec![(4, 98), (5, 101), (6, 102)]);
+		assert_eq!(db_chain_finalized_blocks(&conn, 11).unwrap(), vec![(9, 112)]);
+		assert!(db_chain_finalized_blocks(&conn, 12).unwrap().is_empty());
+	}
+
+	#[test]
+	fn log_row_csv_quoting() {
+		let row = LogRow {
+			slot: 8402,
+			epoch: 7,
+			status: "missed".to_string(),
+			author: Some("0xaa".to_string()),
+			planned_time_utc: "2025-01-01T00:00:12+00:00".to_string(),
+			gap_author: Some("say \"hi\", twice\nok".to_string()),
+			epoch_start_slot: Some(8400),
+			..LogRow::default()
+		};
+		let record = row.to_csv_record();
+		assert!(record.starts_with("7,8402,2,missed,0xaa,2025-01-01T00:00:12+00:00,,,"));
+		assert!(record.contains(",\"say \"\"hi\"\", twice\nok\",8400,"));
+		// One separator per field boundary, plus the comma inside the quoted field.
+		assert_eq!(record.matches(',').count(), LOG_FIELDS.len());
+		let json = row.to_json();
+		assert_eq!(json["slot_in_epoch"], 2);
+		assert_eq!(json["gap_author"], "say \"hi\", twice\nok");
+		assert!(json["block_hash"].is_null());
+	}
+
+	fn slot_sources(conn: &Connection, epoch: u64) -> Vec<(u64, String, String)> {
+		let mut stmt = conn.prepare("SELECT slot, status, source FROM blocks WHERE epoch=?1 ORDER BY slot").unwrap();
+		stmt.query_map(params![epoch as i64], |r| Ok((r.get::<_, i64>(0)? as u64, r.get(1)?, r.get(2)?)))
+			.unwrap()
+			.collect::<Result<_, _>>()
+			.unwrap()
+	}
+
+	#[test]
+	fn next_committee_slots_are_reconciled_at_epoch_start() {
+		let mut conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+		let planned = |slots: &[u64]| -> Vec<(u64, String)> {
+			slots.iter().map(|s| (*s, format!("2025-01-01T00:00:{s:02}+00:00"))).collect()
+		};
+		// Planned ahead from NextCommittee; slot 13 was already produced when the epoch started.
+		db_insert_schedule(&mut conn, 1, "0xaa", &planned(&[11, 13, 15, 17]), COMMITTEE_SOURCE_NEXT).unwrap();
+		db_update_block_status(&conn, 13, 2, "0x02", "2025-01-01T00:00:13+00:00", "mint").unwrap();
+		// Another author's rows are left alone.
+		db_insert_schedule(&mut conn, 1, "0xbb", &planned(&[12]), COMMITTEE_SOURCE_NEXT).unwrap();
+
+		// The epoch's actual schedule keeps 15 and adds 19; 11 and 17 are gone.
+		db_insert_schedule(&mut conn, 1, "0xaa", &planned(&[15, 19]), COMMITTEE_SOURCE_CURRENT).unwrap();
+		assert_eq!(db_drop_stale_next_schedule(&conn, 1, "0xaa").unwrap(), 2);
+
+		let next = COMMITTEE_SOURCE_NEXT.to_string();
+		let current = COMMITTEE_SOURCE_CURRENT.to_string();
+		assert_eq!(
+			slot_sources(&conn, 1),
+			vec![
+				(12, "schedule".to_string(), next.clone()),
+				(13, "mint".to_string(), next),
+				(15, "schedule".to_string(), current.clone()),
+				(19, "schedule".to_string(), current),
+			]
+		);
+	}
+
+	#[test]
+	fn store_errors_are_told_apart() {
+		let conn = Connection::open_in_memory().unwrap();
+		let err = db_set_sync_cursor(&conn, SYNC_CURSOR_BEST, 1).unwrap_err();
+		assert!(is_store_error(&err));
+		assert!(is_store_error(&err.context("scan failed")));
+		assert!(!is_store_error(&anyhow::anyhow!("RPC connection closed")));
+	}
+
+	#[test]
+	fn newer_schema_is_refused() {
+		let conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+		conn.execute(
+			"INSERT INTO schema_version(version, applied_at_utc) VALUES (?1, '2030-01-01T00:00:00+00:00')",
+			params![SCHEMA_VERSION + 1],
+		)
+		.unwrap();
+		let err = ensure_db(&conn).unwrap_err();
+		assert!(err.to_string().contains("newer than this mblog supports"));
+		assert_eq!(db_schema_version(&conn).unwrap(), SCHEMA_VERSION + 1);
+	}
+}
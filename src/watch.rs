@@ -0,0 +1,562 @@
+use anyhow::anyhow;
+use rusqlite::Connection;
+use sp_core::H256;
+use std::collections::BTreeMap;
+use substrate_api_client::ac_primitives::sr25519;
+
+use crate::chain::{aura_slot_from_header, authorities_at, block_onchain_time_utc, ChainSource, Header};
+use crate::render::{format_ts, hex0x, OutputTz};
+use crate::schedule::hash_authorities;
+use crate::store::{
+	db_archive_mint_hash, db_get_sync_cursor, db_mark_finality_seen, db_mark_missed, db_mark_orphaned,
+	db_set_block_times, db_set_sync_cursor, db_update_block_status, db_upsert_chain_block, db_upsert_minted_block,
+	ChainBlock, SYNC_CURSOR_BEST, SYNC_CURSOR_FINALIZED,
+};
+
+/// How many recently scanned best blocks are remembered for reorg detection.
+const SEEN_BEST_WINDOW: usize = 256;
+
+/// While catching up, only blocks at most this far behind the head count as reached live and get
+/// `first_seen_utc` / `finality_seen_utc`; older replayed blocks keep them NULL.
+const LIVE_SEEN_WINDOW: u64 = 3;
+
+/// Chain state read at the best head for one iteration of the watch loop.
+pub struct HeadState {
+	pub auths: Vec<sr25519::Public>,
+	pub auth_hash: [u8; 32],
+	pub slot_dur_ms: u64,
+	pub ts_ms: u64,
+	pub best_number: u64,
+	pub latest_slot: u64,
+	pub epoch: u64,
+	pub start_slot: u64,
+	pub end_slot: u64,
+	/// Authority set length before this head, `None` on the first observation.
+	pub prev_auths_len: Option<usize>,
+	/// Authority set differs from the previous observation (always true on the first one).
+	pub auths_changed: bool,
+	/// Epoch differs from the previous observation (always true on the first one).
+	pub epoch_switched: bool,
+}
+
+/// Cursor state of the watch loop: which best/finalized blocks have already been scanned,
+/// and what the last observed authority set / epoch were.
+pub struct Watcher {
+	/// Monitored Aura keys.
+	authors: Vec<[u8; 32]>,
+	epoch_size: u64,
+	/// `Aura.SlotDuration` is a runtime constant; read once.
+	slot_dur_ms: u64,
+	pub last_best_number: u64,
+	pub last_finalized_number: u64,
+	/// Best-chain hashes already scanned, by number, used to detect reorgs.
+	seen_best: BTreeMap<u64, H256>,
+	prev_auth_hash: Option<[u8; 32]>,
+	prev_auths_len: usize,
+	prev_epoch: Option<u64>,
+	/// Record every walked header (any author) in `chain_blocks`.
+	record_chain: bool,
+	/// The next best/finalized scans replay blocks produced while mblog was down or disconnected.
+	catching_up_best: bool,
+	catching_up_finalized: bool,
+	/// Keep the `sync_state` cursors up to date; only a run that [`Watcher::resume`]d from them does.
+	store_cursors: bool,
+}
+
+impl Watcher {
+	/// Initialize the cursors from the current chain state (no backfill from genesis).
+	pub fn new<C: ChainSource>(src: &C, authors: Vec<[u8; 32]>, epoch_size: u64) -> anyhow::Result<Self> {
+		let last_best_number = src.best_number()?;
+		let last_finalized_number = src.finalized_number()?;
+		let slot_dur_ms = src.slot_duration_ms()?;
+		Ok(Self {
+			authors,
+			epoch_size,
+			slot_dur_ms,
+			last_best_number,
+			last_finalized_number,
+			seen_best: BTreeMap::new(),
+			prev_auth_hash: None,
+			prev_auths_len: 0,
+			prev_epoch: None,
+			record_chain: false,
+			catching_up_best: false,
+			catching_up_finalized: false,
+			store_cursors: false,
+		})
+	}
+
+	/// Also record every header the scans walk, whoever authored it, in `chain_blocks`.
+	pub fn set_record_chain(&mut self, record_chain: bool) {
+		self.record_chain = record_chain;
+	}
+
+	/// Treat the next scans as a replay (after a restart or reconnect): blocks well behind the head were
+	/// not seen live, so they get no seen times.
+	pub fn catch_up(&mut self) {
+		self.catching_up_best = true;
+		self.catching_up_finalized = true;
+	}
+
+	/// Resume from the cursors stored in `sync_state` by a previous run, so blocks produced while
+	/// mblog was down are backfilled by the next scans. At most `max_backfill` blocks behind the
+	/// current heads are rescanned; returns the number of best blocks that will be backfilled. From then on
+	/// the scans keep the stored cursors up to date.
+	pub fn resume(&mut self, conn: &Connection, max_backfill: u64) -> anyhow::Result<u64> {
+		let resume_from = |stored: Option<u64>, current: u64| match stored {
+			Some(n) => n.clamp(current.saturating_sub(max_backfill), current),
+			None => current,
+		};
+		let best = resume_from(db_get_sync_cursor(conn, SYNC_CURSOR_BEST)?, self.last_best_number);
+		let finalized = resume_from(db_get_sync_cursor(conn, SYNC_CURSOR_FINALIZED)?, self.last_finalized_number);
+		let backfill = self.last_best_number - best;
+		self.last_best_number = best;
+		self.last_finalized_number = finalized;
+		db_set_sync_cursor(conn, SYNC_CURSOR_BEST, best)?;
+		db_set_sync_cursor(conn, SYNC_CURSOR_FINALIZED, finalized)?;
+		self.store_cursors = true;
+		self.catch_up();
+		Ok(backfill)
+	}
+
+	pub fn epoch_size(&self) -> u64 {
+		self.epoch_size
+	}
+
+	/// Poll the best head, then [`Watcher::observe_header`] it.
+	pub fn observe_head<C: ChainSource>(&mut self, src: &C) -> anyhow::Result<HeadState> {
+		let best_hash = src.best_hash()?.ok_or_else(|| anyhow!("no best head"))?;
+		let best_header = src.header(best_hash)?.ok_or_else(|| anyhow!("no best header"))?;
+		self.observe_header(src, &best_header)
+	}
+
+	/// Read authorities and timestamp at a (new) best header, and work out the epoch.
+	pub fn observe_header<C: ChainSource>(&mut self, src: &C, best_header: &Header) -> anyhow::Result<HeadState> {
+		let best_hash = best_header.hash();
+		let auths = src.authorities(Some(best_hash))?;
+		let auth_hash = hash_authorities(&auths);
+		let auths_changed = self.prev_auth_hash != Some(auth_hash) || self.prev_auths_len != auths.len();
+		let prev_auths_len = self.prev_auth_hash.map(|_| self.prev_auths_len);
+		self.prev_auth_hash = Some(auth_hash);
+		self.prev_auths_len = auths.len();
+
+		let slot_dur_ms = self.slot_dur_ms;
+		let ts_ms = src.timestamp_ms(Some(best_hash))?.unwrap_or(0);
+
+		// Prefer the real Aura slot from the block digest; timestamp/slot_duration is only a fallback.
+		let latest_slot = aura_slot_from_header(best_header).unwrap_or_else(|| ts_ms / slot_dur_ms.max(1));
+		let best_number: u64 = best_header.number.into();
+		let epoch = latest_slot / self.epoch_size;
+		let start_slot = epoch * self.epoch_size;
+		let end_slot = start_slot + self.epoch_size.saturating_sub(1);
+
+		let epoch_switched = self.prev_epoch != Some(epoch);
+		self.prev_epoch = Some(epoch);
+
+		Ok(HeadState {
+			auths,
+			auth_hash,
+			slot_dur_ms,
+			ts_ms,
+			best_number,
+			latest_slot,
+			epoch,
+			start_slot,
+			end_slot,
+			prev_auths_len,
+			auths_changed,
+			epoch_switched,
+		})
+	}
+
+	/// Mint detection: scan new best blocks since the last check and record blocks produced by any monitored author.
+	/// Scanning every block (not just the head) avoids missing mint events between polls.
+	pub fn scan_minted<C: ChainSource>(
+		&mut self,
+		src: &C,
+		conn: Option<&Connection>,
+		head: &HeadState,
+	) -> anyhow::Result<()> {
+		let catching_up = std::mem::take(&mut self.catching_up_best);
+		// Rewind over scanned blocks whose canonical hash changed (reorg) so the new fork is rescanned.
+		let mut start = self.last_best_number + 1;
+		while let Some((&n, &seen)) = self.seen_best.range(..start).next_back() {
+			if src.block_hash(n)? == Some(seen) {
+				break;
+			}
+			self.seen_best.remove(&n);
+			start = n;
+		}
+		if start > head.best_number {
+			return Ok(());
+		}
+		for n in start..=head.best_number {
+			let Some((h, hdr)) = src.header_by_number(n)? else {
+				continue;
+			};
+			self.seen_best.insert(n, h);
+			let Some(slot) = aura_slot_from_header(&hdr) else {
+				continue;
+			};
+			let auths_for_slot = authorities_at(src, hdr.parent_hash).unwrap_or_else(|_| head.auths.clone());
+			if self.record_chain
+				&& let Some(c) = conn
+			{
+				db_upsert_chain_block(c, &chain_block(src, n, h, slot, self.epoch_size, &auths_for_slot, false))?;
+			}
+			if auths_for_slot.is_empty() {
+				continue;
+			}
+			let expected = &auths_for_slot[(slot as usize) % auths_for_slot.len()];
+			let expected_bytes: &[u8] = expected.as_ref();
+			if !self.authors.iter().any(|a| a.as_slice() == expected_bytes) {
+				continue;
+			}
+			if let Some(c) = conn {
+				let now_utc = chrono::Utc::now().to_rfc3339();
+				let live = !catching_up || head.best_number - n <= LIVE_SEEN_WINDOW;
+				let block_hash_str = format!("{h:?}");
+				let onchain_time_utc = block_onchain_time_utc(src, h);
+				let produced_time_utc = onchain_time_utc.clone().unwrap_or_else(|| now_utc.clone());
+				let author = hex0x(expected_bytes);
+				let epoch = slot / self.epoch_size;
+				db_upsert_minted_block(c, slot, epoch, n, &block_hash_str, &produced_time_utc, &author)?;
+				db_set_block_times(
+					c,
+					slot,
+					&block_hash_str,
+					&slot_start_utc(slot, self.slot_dur_ms),
+					onchain_time_utc.as_deref(),
+					live.then_some(now_utc.as_str()),
+				)?;
+			}
+		}
+		self.last_best_number = head.best_number;
+		if let Some(c) = conn
+			&& self.store_cursors
+		{
+			db_set_sync_cursor(c, SYNC_CURSOR_BEST, self.last_best_number)?;
+		}
+		while self.seen_best.len() > SEEN_BEST_WINDOW {
+			self.seen_best.pop_first();
+		}
+		Ok(())
+	}
+
+	/// Finality: scan new finalized blocks since the last check and update scheduled slots.
+	pub fn scan_finalized<C: ChainSource>(&mut self, src: &C, conn: Option<&Connection>) -> anyhow::Result<bool> {
+		let chain_epoch_size = self.record_chain.then_some(self.epoch_size);
+		let catching_up = std::mem::take(&mut self.catching_up_finalized);
+		let scanned =
+			scan_new_finalized_blocks(src, conn, &mut self.last_finalized_number, chain_epoch_size, catching_up)?;
+		self.store_finalized_cursor(conn, scanned)?;
+		Ok(scanned)
+	}
+
+	/// Like [`Watcher::scan_finalized`], for a finalized head that is already known (subscription).
+	pub fn scan_finalized_to<C: ChainSource>(
+		&mut self,
+		src: &C,
+		conn: Option<&Connection>,
+		finalized_number: u64,
+	) -> anyhow::Result<bool> {
+		let chain_epoch_size = self.record_chain.then_some(self.epoch_size);
+		let catching_up = std::mem::take(&mut self.catching_up_finalized);
+		let scanned = scan_finalized_blocks_to(
+			src,
+			conn,
+			&mut self.last_finalized_number,
+			finalized_number,
+			chain_epoch_size,
+			catching_up,
+		)?;
+		self.store_finalized_cursor(conn, scanned)?;
+		Ok(scanned)
+	}
+
+	fn store_finalized_cursor(&self, conn: Option<&Connection>, scanned: bool) -> anyhow::Result<()> {
+		if let (Some(c), true, true) = (conn, scanned, self.store_cursors) {
+			db_set_sync_cursor(c, SYNC_CURSOR_FINALIZED, self.last_finalized_number)?;
+		}
+		Ok(())
+	}
+}
+
+/// `chain_epoch_size`: when set, every walked header is also recorded in `chain_blocks` (epochs of that size).
+/// `catching_up`: the range replays blocks finalized while mblog was not watching; only the last
+/// [`LIVE_SEEN_WINDOW`] blocks get `finality_seen_utc`.
+pub fn scan_new_finalized_blocks<C: ChainSource>(
+	src: &C,
+	conn: Option<&Connection>,
+	last_finalized_number: &mut u64,
+	chain_epoch_size: Option<u64>,
+	catching_up: bool,
+) -> anyhow::Result<bool> {
+	let Some(finalized_hash) = src.finalized_hash()? else {
+		return Ok(false);
+	};
+	let Some(finalized_header) = src.header(finalized_hash)? else {
+		return Ok(false);
+	};
+
+	scan_finalized_blocks_to(
+		src,
+		conn,
+		last_finalized_number,
+		finalized_header.number.into(),
+		chain_epoch_size,
+		catching_up,
+	)
+}
+
+pub fn scan_finalized_blocks_to<C: ChainSource>(
+	src: &C,
+	conn: Option<&Connection>,
+	last_finalized_number: &mut u64,
+	finalized_number: u64,
+	chain_epoch_size: Option<u64>,
+	catching_up: bool,
+) -> anyhow::Result<bool> {
+	if finalized_number <= *last_finalized_number {
+		return Ok(false);
+	}
+
+	// If we don't store anything, just advance the cursor to avoid repeated scans.
+	let Some(conn) = conn else {
+		*last_finalized_number = finalized_number;
+		return Ok(true);
+	};
+
+	let seen_utc = chrono::Utc::now().to_rfc3339();
+	let mut prev_slot = src
+		.header_by_number(*last_finalized_number)?
+		.and_then(|(_, hdr)| aura_slot_from_header(&hdr));
+	for n in (*last_finalized_number + 1)..=finalized_number {
+		let Some((h, hdr)) = src.header_by_number(n)? else {
+			continue;
+		};
+		let Some(slot) = aura_slot_from_header(&hdr) else {
+			continue;
+		};
+		record_finalized_block(src, conn, n, h, &hdr, slot, prev_slot)?;
+		if let Some(epoch_size) = chain_epoch_size {
+			let auths = authorities_at(src, hdr.parent_hash).unwrap_or_default();
+			db_upsert_chain_block(conn, &chain_block(src, n, h, slot, epoch_size, &auths, true))?;
+		}
+		prev_slot = Some(slot);
+	}
+	let mut seen_from = *last_finalized_number + 1;
+	if catching_up {
+		seen_from = seen_from.max(finalized_number.saturating_sub(LIVE_SEEN_WINDOW));
+	}
+	db_mark_finality_seen(conn, seen_from, finalized_number, finalized_number, &seen_utc)?;
+
+	*last_finalized_number = finalized_number;
+	Ok(true)
+}
+
+/// Apply one finalized block (authored in `slot`) to the `blocks` table: finality for our slot, and
+/// `missed`/`orphaned` for our slots skipped since the previous finalized block's `prev_slot`.
+pub fn record_finalized_block<C: ChainSource>(
+	src: &C,
+	conn: &Connection,
+	number: u64,
+	hash: H256,
+	hdr: &Header,
+	slot: u64,
+	prev_slot: Option<u64>,
+) -> anyhow::Result<()> {
+	let block_hash_str = format!("{hash:?}");
+	let onchain_time_utc = block_onchain_time_utc(src, hash);
+	let produced_time_utc = onchain_time_utc.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+	// Our block for this slot on another fork lost to the canonical one.
+	db_archive_mint_hash(conn, slot, &block_hash_str, Some(&block_hash_str))?;
+	db_update_block_status(conn, slot, number, &block_hash_str, &produced_time_utc, "finality")?;
+	let slot_start = slot_start_utc(slot, src.slot_duration_ms()?);
+	db_set_block_times(conn, slot, &block_hash_str, &slot_start, onchain_time_utc.as_deref(), None)?;
+
+	// Slots skipped between two consecutive finalized blocks were never produced on the canonical chain.
+	if let Some(prev) = prev_slot
+		&& slot > prev + 1
+	{
+		let gap_author = authorities_at(src, hdr.parent_hash)
+			.ok()
+			.filter(|a| !a.is_empty())
+			.map(|a| hex0x(a[(slot as usize) % a.len()].as_ref()));
+		db_mark_missed(conn, prev, slot, number, gap_author.as_deref())?;
+		db_mark_orphaned(conn, prev, slot)?;
+	}
+	Ok(())
+}
+
+/// `chain_blocks` row for a walked header; `auths` is the authority set at its parent.
+fn chain_block<C: ChainSource>(
+	src: &C,
+	number: u64,
+	hash: H256,
+	slot: u64,
+	epoch_size: u64,
+	auths: &[sr25519::Public],
+	finalized: bool,
+) -> ChainBlock {
+	ChainBlock {
+		block_number: number,
+		block_hash: format!("{hash:?}"),
+		slot,
+		epoch: slot / epoch_size,
+		author: (!auths.is_empty()).then(|| hex0x(auths[(slot as usize) % auths.len()].as_ref())),
+		timestamp_utc: block_onchain_time_utc(src, hash),
+		authority_set_len: auths.len(),
+		finalized,
+	}
+}
+
+/// Exact start of `slot`: Aura slots are counted from the Unix epoch, so it is `slot * SlotDuration`.
+pub fn slot_start_utc(slot: u64, slot_dur_ms: u64) -> String {
+	format_ts((slot * slot_dur_ms) as i64, &OutputTz::Utc)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chain::scripted::ScriptedChain;
+	use crate::store::{db_insert_schedule, ensure_db, SCHEDULE_SOURCE_AURA};
+	use rusqlite::params;
+
+	const SLOT_DUR_MS: u64 = 6000;
+	const EPOCH_SIZE: u64 = 12;
+
+	fn key(b: u8) -> sr25519::Public {
+		sr25519::Public::from_raw([b; 32])
+	}
+
+	/// Two authorities: ours (`0x01..`) authors the even slots.
+	fn setup() -> (ScriptedChain, Connection, Watcher) {
+		let src = ScriptedChain::new(SLOT_DUR_MS, vec![key(1), key(2)]);
+		let conn = Connection::open_in_memory().unwrap();
+		ensure_db(&conn).unwrap();
+		let watcher = Watcher::new(&src, vec![[1u8; 32]], EPOCH_SIZE).unwrap();
+		(src, conn, watcher)
+	}
+
+	fn schedule(conn: &mut Connection, slots: &[u64]) {
+		let planned: Vec<(u64, String)> = slots.iter().map(|&s| (s, slot_start_utc(s, SLOT_DUR_MS))).collect();
+		db_insert_schedule(conn, 0, &hex0x(&[1u8; 32]), &planned, SCHEDULE_SOURCE_AURA).unwrap();
+	}
+
+	fn scan(src: &ScriptedChain, conn: &Connection, watcher: &mut Watcher) {
+		let head = watcher.observe_head(src).unwrap();
+		watcher.scan_minted(src, Some(conn), &head).unwrap();
+		watcher.scan_finalized(src, Some(conn)).unwrap();
+	}
+
+	fn status(conn: &Connection, slot: u64) -> Option<String> {
+		conn.query_row("SELECT status FROM blocks WHERE slot=?1", params![slot as i64], |r| r.get(0)).ok()
+	}
+
+	fn first_seen(conn: &Connection, slot: u64) -> Option<String> {
+		conn.query_row("SELECT first_seen_utc FROM blocks WHERE slot=?1", params![slot as i64], |r| r.get(0))
+			.unwrap()
+	}
+
+	fn orphaned_rows(conn: &Connection, slot: u64) -> u64 {
+		conn.query_row("SELECT COUNT(*) FROM orphaned_blocks WHERE slot=?1", params![slot as i64], |r| {
+			r.get::<_, i64>(0)
+		})
+		.unwrap() as u64
+	}
+
+	#[test]
+	fn mint_then_finality() {
+		let (mut src, mut conn, mut watcher) = setup();
+		schedule(&mut conn, &[2, 4]);
+		src.push_block(1);
+		src.push_block(2);
+		scan(&src, &conn, &mut watcher);
+		assert_eq!(status(&conn, 2).as_deref(), Some("mint"));
+		assert!(first_seen(&conn, 2).is_some());
+
+		src.push_block(3);
+		src.finalize(3);
+		scan(&src, &conn, &mut watcher);
+		assert_eq!(status(&conn, 2).as_deref(), Some("finality"));
+		assert_eq!(status(&conn, 4).as_deref(), Some("schedule"));
+		assert_eq!(watcher.last_finalized_number, 3);
+	}
+
+	#[test]
+	fn skipped_slot_is_missed() {
+		let (mut src, mut conn, mut watcher) = setup();
+		schedule(&mut conn, &[2, 4]);
+		src.push_block(1);
+		src.push_block(3);
+		src.push_block(4);
+		src.finalize(3);
+		scan(&src, &conn, &mut watcher);
+		assert_eq!(status(&conn, 2).as_deref(), Some("missed"));
+		assert_eq!(status(&conn, 4).as_deref(), Some("finality"));
+	}
+
+	#[test]
+	fn reorged_mint_is_orphaned() {
+		let (mut src, mut conn, mut watcher) = setup();
+		schedule(&mut conn, &[2, 4, 6, 8, 10]);
+		for slot in [1, 3, 4, 5, 7, 8] {
+			src.push_block(slot);
+			scan(&src, &conn, &mut watcher);
+		}
+		assert_eq!(status(&conn, 8).as_deref(), Some("mint"));
+
+		// Block #6 (slot 8) loses to a fork that skips slots 8 and 10.
+		src.rewind(5);
+		src.push_block(9);
+		src.push_block(11);
+		src.finalize(7);
+		scan(&src, &conn, &mut watcher);
+
+		assert_eq!(status(&conn, 2).as_deref(), Some("missed"));
+		assert_eq!(status(&conn, 4).as_deref(), Some("finality"));
+		assert_eq!(status(&conn, 6).as_deref(), Some("missed"));
+		assert_eq!(status(&conn, 8).as_deref(), Some("orphaned"));
+		assert_eq!(status(&conn, 10).as_deref(), Some("missed"));
+		assert_eq!(orphaned_rows(&conn, 8), 1);
+	}
+
+	#[test]
+	fn scans_without_resume_leave_cursors_alone() {
+		let (mut src, conn, mut watcher) = setup();
+		db_set_sync_cursor(&conn, SYNC_CURSOR_BEST, 0).unwrap();
+		for slot in 1..=4 {
+			src.push_block(slot);
+		}
+		src.finalize(4);
+		scan(&src, &conn, &mut watcher);
+		assert_eq!(db_get_sync_cursor(&conn, SYNC_CURSOR_BEST).unwrap(), Some(0));
+		assert_eq!(db_get_sync_cursor(&conn, SYNC_CURSOR_FINALIZED).unwrap(), None);
+	}
+
+	#[test]
+	fn resume_is_bounded_by_max_backfill() {
+		let (mut src, conn, mut watcher) = setup();
+		assert_eq!(watcher.resume(&conn, 5).unwrap(), 0);
+		scan(&src, &conn, &mut watcher);
+
+		// mblog is down while 20 blocks are produced.
+		for slot in 1..=20 {
+			src.push_block(slot);
+		}
+		let mut watcher = Watcher::new(&src, vec![[1u8; 32]], EPOCH_SIZE).unwrap();
+		assert_eq!(watcher.resume(&conn, 5).unwrap(), 5);
+		assert_eq!(watcher.last_best_number, 15);
+		scan(&src, &conn, &mut watcher);
+
+		assert_eq!(status(&conn, 14), None);
+		for slot in [16, 18, 20] {
+			assert_eq!(status(&conn, slot).as_deref(), Some("mint"));
+		}
+		// Replayed blocks well behind the head were not seen live.
+		assert_eq!(first_seen(&conn, 16), None);
+		assert!(first_seen(&conn, 18).is_some());
+		assert_eq!(watcher.last_best_number, 20);
+	}
+}